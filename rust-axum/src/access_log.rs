@@ -0,0 +1,260 @@
+use std::{fmt::Write, net::SocketAddr, str::FromStr, sync::Arc, time::Instant};
+
+use std::sync::Mutex;
+
+use axum::{
+    body::HttpBody,
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use http::header::CONTENT_LENGTH;
+use serde::{Deserialize, Serialize};
+
+use crate::request_id::RequestId;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessRecord {
+    pub method: String,
+    pub route: Option<String>,
+    pub status: u16,
+    pub latency_ms: f64,
+    pub size: Option<u64>,
+    pub client: Option<String>,
+    pub request_id: Option<String>,
+}
+
+impl AccessRecord {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn to_logfmt(&self) -> String {
+        let mut line = String::new();
+        let mut field = |key: &str, value: Option<String>| {
+            let value = value.unwrap_or_default();
+            if !line.is_empty() {
+                line.push(' ');
+            }
+
+            let plain = |c: char| !c.is_whitespace() && !c.is_control() && c != '"' && c != '=';
+            if value.is_empty() || !value.chars().all(plain) {
+                write!(line, "{}={:?}", key, value).unwrap();
+            } else {
+                write!(line, "{}={}", key, value).unwrap();
+            }
+        };
+
+        field("method", Some(self.method.clone()));
+        field("route", self.route.clone());
+        field("status", Some(self.status.to_string()));
+        field("latency_ms", Some(format!("{:.3}", self.latency_ms)));
+        field("size", self.size.map(|size| size.to_string()));
+        field("client", self.client.clone());
+        field("request_id", self.request_id.clone());
+
+        line
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Json,
+    Logfmt,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "json" => Ok(LogFormat::Json),
+            "logfmt" => Ok(LogFormat::Logfmt),
+            other => Err(format!(
+                "unknown log format {}, expected json or logfmt",
+                other
+            )),
+        }
+    }
+}
+
+pub trait AccessLogSink: Send + Sync {
+    fn record(&self, record: AccessRecord);
+}
+
+pub struct StdoutSink {
+    format: LogFormat,
+}
+
+impl StdoutSink {
+    pub fn new(format: LogFormat) -> Self {
+        Self { format }
+    }
+}
+
+impl AccessLogSink for StdoutSink {
+    fn record(&self, record: AccessRecord) {
+        match self.format {
+            LogFormat::Json => println!("{}", record.to_json()),
+            LogFormat::Logfmt => println!("{}", record.to_logfmt()),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MemorySink {
+    records: Mutex<Vec<AccessRecord>>,
+}
+
+impl MemorySink {
+    pub fn records(&self) -> Vec<AccessRecord> {
+        self.records.lock().unwrap().clone()
+    }
+}
+
+impl AccessLogSink for MemorySink {
+    fn record(&self, record: AccessRecord) {
+        self.records.lock().unwrap().push(record);
+    }
+}
+
+pub async fn log_middleware(
+    State(sink): State<Arc<dyn AccessLogSink>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let start = Instant::now();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string());
+    let client = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(address)| address.to_string());
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .map(|id| id.to_string());
+
+    let response = next.run(request).await;
+
+    let size = response
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .or_else(|| response.body().size_hint().exact());
+
+    sink.record(AccessRecord {
+        method,
+        route,
+        status: response.status().as_u16(),
+        latency_ms: start.elapsed().as_secs_f64() * 1000.0,
+        size,
+        client,
+        request_id,
+    });
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::SocketAddr, sync::Arc};
+
+    use axum::{
+        Router,
+        middleware::{from_fn, from_fn_with_state},
+        routing::get,
+    };
+    use axum_test::TestServer;
+
+    use super::{AccessLogSink, AccessRecord, LogFormat, MemorySink, log_middleware};
+    use crate::request_id::request_id_middleware;
+
+    fn server(sink: Arc<MemorySink>) -> TestServer {
+        let sink: Arc<dyn AccessLogSink> = sink;
+        let app = Router::new()
+            .route("/products/{id}", get(|| async { "Hello, World!" }))
+            .layer(from_fn_with_state(sink, log_middleware))
+            .layer(from_fn(request_id_middleware));
+
+        TestServer::new(app.into_make_service_with_connect_info::<SocketAddr>()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_access_log_record() {
+        let sink = Arc::new(MemorySink::default());
+        let server = server(sink.clone());
+
+        let response = server
+            .get("/products/10")
+            .add_header("X-Request-ID", "request-1")
+            .await;
+        response.assert_status_ok();
+
+        let records = sink.records();
+        assert_eq!(records.len(), 1);
+
+        let record = &records[0];
+        assert_eq!(record.method, "GET");
+        assert_eq!(record.route.as_deref(), Some("/products/{id}"));
+        assert_eq!(record.status, 200);
+        assert_eq!(record.size, Some(13));
+        assert!(record.latency_ms >= 0.0);
+        assert!(record.client.as_deref().unwrap().starts_with("127.0.0.1:"));
+        assert_eq!(record.request_id.as_deref(), Some("request-1"));
+    }
+
+    #[tokio::test]
+    async fn test_access_log_unmatched_route() {
+        let sink = Arc::new(MemorySink::default());
+        let server = server(sink.clone());
+
+        server.get("/wrong").await.assert_status_not_found();
+
+        let records = sink.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].route, None);
+        assert_eq!(records[0].status, 404);
+    }
+
+    #[test]
+    fn test_access_log_format() {
+        let record = AccessRecord {
+            method: "GET".to_string(),
+            route: Some("/products/{id}".to_string()),
+            status: 200,
+            latency_ms: 1.5,
+            size: Some(13),
+            client: None,
+            request_id: Some("request-1".to_string()),
+        };
+
+        assert_eq!(
+            record.to_logfmt(),
+            "method=GET route=/products/{id} status=200 latency_ms=1.500 size=13 client=\"\" request_id=request-1"
+        );
+
+        let forged = AccessRecord {
+            request_id: Some("a\nb".to_string()),
+            client: Some("\u{1b}[31m\tx".to_string()),
+            ..record.clone()
+        };
+        let line = forged.to_logfmt();
+        assert!(!line.contains('\n') && !line.contains('\u{1b}'));
+        assert!(line.ends_with(r#"client="\u{1b}[31m\tx" request_id="a\nb""#));
+
+        let json: serde_json::Value = serde_json::from_str(&record.to_json()).unwrap();
+        assert_eq!(json["route"], "/products/{id}");
+        assert_eq!(json["status"], 200);
+        assert_eq!(json["client"], serde_json::Value::Null);
+
+        assert_eq!("logfmt".parse::<LogFormat>(), Ok(LogFormat::Logfmt));
+        assert_eq!("JSON".parse::<LogFormat>(), Ok(LogFormat::Json));
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+}
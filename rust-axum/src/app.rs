@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    middleware::{from_fn, from_fn_with_state},
+};
+use axum_extra::extract::cookie::Key;
+use utoipa::OpenApi;
+use utoipa_axum::router::OpenApiRouter;
+
+use crate::{
+    access_log::{AccessLogSink, LogFormat, StdoutSink, log_middleware},
+    assets::{self, AssetSettings},
+    auth::{self, ApiKeys, TokenService},
+    brand, category,
+    conditional::conditional_middleware,
+    health::{self, HealthChecks},
+    idempotency::{Idempotency, idempotency_middleware},
+    jobs::{self, JobRegistry},
+    live::{self, EventBus, LiveSettings},
+    metrics::{self, Metrics, metrics_middleware},
+    openapi::{self, ApiDoc},
+    rate_limit::{RateLimiter, rate_limit_middleware},
+    repository::{BrandRepository, CategoryRepository, SessionStore, UserRepository},
+    request_id::request_id_middleware,
+    security::{self, SecuritySettings},
+    session::{self, SessionSettings},
+    shutdown::Shutdown,
+    timeout::{Timeouts, timeout_middleware},
+    upload::{self, FileStorage, LocalStorage, UploadLimits},
+    view::Templates,
+};
+
+#[derive(Clone)]
+pub struct AppState {
+    pub categories: Arc<dyn CategoryRepository>,
+    pub brands: Arc<dyn BrandRepository>,
+    pub users: Arc<dyn UserRepository>,
+    pub sessions: Arc<dyn SessionStore>,
+    pub tokens: Arc<TokenService>,
+    pub api_keys: Arc<ApiKeys>,
+    pub access_log: Arc<dyn AccessLogSink>,
+    pub storage: Arc<dyn FileStorage>,
+    pub upload_limits: UploadLimits,
+    pub cookie_key: Key,
+    pub session_settings: SessionSettings,
+    pub health: Arc<HealthChecks>,
+    pub metrics: Arc<Metrics>,
+    pub rate_limiter: Arc<RateLimiter>,
+    pub security: Arc<SecuritySettings>,
+    pub events: Arc<EventBus>,
+    pub live_settings: LiveSettings,
+    pub jobs: Arc<JobRegistry>,
+    pub templates: Arc<Templates>,
+    pub assets: AssetSettings,
+    pub idempotency: Arc<Idempotency>,
+    pub timeouts: Arc<Timeouts>,
+    pub shutdown: Shutdown,
+}
+
+impl AppState {
+    pub fn new<R>(repository: Arc<R>, tokens: TokenService) -> Self
+    where
+        R: CategoryRepository + BrandRepository + UserRepository + SessionStore + 'static,
+    {
+        Self {
+            categories: repository.clone(),
+            brands: repository.clone(),
+            users: repository.clone(),
+            sessions: repository,
+            tokens: Arc::new(tokens),
+            api_keys: Arc::new(ApiKeys::default()),
+            access_log: Arc::new(StdoutSink::new(LogFormat::Json)),
+            storage: Arc::new(LocalStorage::new("uploads")),
+            upload_limits: UploadLimits::default(),
+            cookie_key: Key::generate(),
+            session_settings: SessionSettings::default(),
+            health: Arc::new(HealthChecks::default()),
+            metrics: Arc::new(Metrics::new()),
+            rate_limiter: Arc::new(RateLimiter::default()),
+            security: Arc::new(SecuritySettings::default()),
+            events: Arc::new(EventBus::default()),
+            live_settings: LiveSettings::default(),
+            jobs: Arc::new(JobRegistry::default()),
+            templates: Arc::new(Templates::default()),
+            assets: AssetSettings::default(),
+            idempotency: Arc::new(Idempotency::default()),
+            timeouts: Arc::new(Timeouts::default()),
+            shutdown: Shutdown::new(),
+        }
+    }
+
+    pub fn with_api_keys(mut self, api_keys: ApiKeys) -> Self {
+        self.api_keys = Arc::new(api_keys);
+        self
+    }
+
+    pub fn with_access_log(mut self, sink: Arc<dyn AccessLogSink>) -> Self {
+        self.access_log = sink;
+        self
+    }
+
+    pub fn with_storage(mut self, storage: Arc<dyn FileStorage>, limits: UploadLimits) -> Self {
+        self.storage = storage;
+        self.upload_limits = limits;
+        self
+    }
+
+    pub fn with_sessions(mut self, key: Key, settings: SessionSettings) -> Self {
+        self.cookie_key = key;
+        self.session_settings = settings;
+        self
+    }
+
+    pub fn with_health_checks(mut self, health: HealthChecks) -> Self {
+        self.health = Arc::new(health);
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Arc::new(rate_limiter);
+        self
+    }
+
+    pub fn with_security(mut self, settings: SecuritySettings) -> Self {
+        self.security = Arc::new(settings);
+        self
+    }
+
+    pub fn with_live(mut self, events: EventBus, settings: LiveSettings) -> Self {
+        self.events = Arc::new(events);
+        self.live_settings = settings;
+        self
+    }
+
+    pub fn with_jobs(mut self, jobs: JobRegistry) -> Self {
+        self.jobs = Arc::new(jobs);
+        self
+    }
+
+    pub fn with_templates(mut self, templates: Arc<Templates>) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    pub fn with_assets(mut self, settings: AssetSettings) -> Self {
+        self.assets = settings;
+        self
+    }
+
+    pub fn with_idempotency(mut self, idempotency: Idempotency) -> Self {
+        self.idempotency = Arc::new(idempotency);
+        self
+    }
+
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = Arc::new(timeouts);
+        self
+    }
+
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+}
+
+pub fn api_routes(state: AppState) -> OpenApiRouter<AppState> {
+    OpenApiRouter::with_openapi(ApiDoc::openapi())
+        .nest("/api/categories", category::routes())
+        .nest("/api/brands", brand::routes())
+        .nest("/api/users", auth::routes(state.clone()))
+        .nest("/api/sessions", session::routes())
+        .nest("/api/uploads", upload::routes(state))
+        .nest("/api/live", live::routes())
+        .nest("/api/jobs", jobs::routes())
+        .nest("/health", health::routes())
+        .merge(metrics::routes())
+}
+
+pub fn router(state: AppState) -> Router {
+    let (api, openapi) = api_routes(state.clone()).split_for_parts();
+
+    let api = api
+        .merge(openapi::routes(openapi))
+        .merge(brand::pages())
+        .merge(category::pages())
+        .layer(from_fn_with_state(state.clone(), timeout_middleware))
+        .layer(from_fn_with_state(state.clone(), idempotency_middleware))
+        .layer(from_fn(conditional_middleware))
+        .layer(from_fn_with_state(state.clone(), rate_limit_middleware))
+        .merge(assets::routes(&state.assets));
+
+    security::layers(api, &state.security)
+        .layer(from_fn_with_state(state.access_log.clone(), log_middleware))
+        .layer(from_fn_with_state(
+            state.metrics.clone(),
+            metrics_middleware,
+        ))
+        .layer(from_fn(request_id_middleware))
+        .with_state(state)
+}
@@ -0,0 +1,247 @@
+use std::{path::PathBuf, time::Duration};
+
+use axum::{
+    Router,
+    extract::{Request, State},
+    middleware::{Next, from_fn_with_state},
+    response::{IntoResponse, Response},
+};
+use http::{
+    HeaderValue, StatusCode,
+    header::{CACHE_CONTROL, VARY},
+};
+use tower_http::services::ServeDir;
+
+use crate::error::AppError;
+
+#[derive(Debug, Clone)]
+pub struct AssetSettings {
+    pub directory: PathBuf,
+    pub max_age: Duration,
+    pub immutable_max_age: Duration,
+}
+
+impl Default for AssetSettings {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("assets"),
+            max_age: Duration::from_secs(300),
+            immutable_max_age: Duration::from_secs(31536000),
+        }
+    }
+}
+
+impl AssetSettings {
+    fn cache_control(&self, path: &str) -> String {
+        if is_fingerprinted(path) {
+            format!(
+                "public, max-age={}, immutable",
+                self.immutable_max_age.as_secs()
+            )
+        } else {
+            format!("public, max-age={}", self.max_age.as_secs())
+        }
+    }
+}
+
+pub fn is_fingerprinted(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let mut parts: Vec<&str> = name.split(['.', '-']).collect();
+    if parts.len() < 3 {
+        return false;
+    }
+    parts.pop();
+
+    parts[1..].iter().any(|part| {
+        part.len() >= 8
+            && part.chars().all(|c| c.is_ascii_hexdigit())
+            && part.chars().any(|c| c.is_ascii_digit())
+    })
+}
+
+fn decode(path: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut input = path.bytes();
+
+    while let Some(byte) = input.next() {
+        if byte == b'%' {
+            let hex = [input.next()?, input.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+pub fn is_safe_path(path: &str) -> bool {
+    decode(path).is_some_and(|path| {
+        !path.contains(['\\', '\0'])
+            && path
+                .split('/')
+                .all(|segment| segment != ".." && segment != "." && !segment.contains(':'))
+    })
+}
+
+async fn asset_middleware(
+    State(settings): State<AssetSettings>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+    if !is_safe_path(&path) {
+        return AppError::new(StatusCode::BAD_REQUEST, "Asset path is invalid").into_response();
+    }
+
+    let mut response = next.run(request).await;
+    if response.status().is_success() || response.status() == StatusCode::NOT_MODIFIED {
+        let headers = response.headers_mut();
+        if let Ok(value) = HeaderValue::from_str(&settings.cache_control(&path)) {
+            headers.insert(CACHE_CONTROL, value);
+        }
+        headers.insert(VARY, HeaderValue::from_static("accept-encoding"));
+    }
+
+    response
+}
+
+pub fn routes<S>(settings: &AssetSettings) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let service = ServeDir::new(&settings.directory)
+        .precompressed_br()
+        .precompressed_gzip()
+        .append_index_html_on_directories(false);
+
+    Router::new()
+        .nest_service("/assets", service)
+        .layer(from_fn_with_state(settings.clone(), asset_middleware))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, sync::Arc, time::Duration};
+
+    use axum_test::TestServer;
+    use http::StatusCode;
+    use tempfile::TempDir;
+
+    use super::{AssetSettings, is_fingerprinted, is_safe_path};
+    use crate::{
+        app::{AppState, router},
+        auth::TokenService,
+        repository::MemoryRepository,
+    };
+
+    fn server() -> (TestServer, TempDir) {
+        let directory = tempfile::tempdir().unwrap();
+        fs::write(
+            directory.path().join("app.3f9a1c2b.css"),
+            "body { margin: 0 }",
+        )
+        .unwrap();
+        fs::write(directory.path().join("app.3f9a1c2b.css.br"), "brotli").unwrap();
+        fs::write(directory.path().join("app.3f9a1c2b.css.gz"), "gzip").unwrap();
+        fs::write(directory.path().join("logo.txt"), "0123456789").unwrap();
+
+        let state = AppState::new(
+            Arc::new(MemoryRepository::new()),
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        )
+        .with_assets(AssetSettings {
+            directory: directory.path().to_path_buf(),
+            ..AssetSettings::default()
+        });
+
+        (TestServer::new(router(state)).unwrap(), directory)
+    }
+
+    #[test]
+    fn test_asset_paths() {
+        assert!(is_fingerprinted("/assets/app.3f9a1c2b.css"));
+        assert!(is_fingerprinted("/assets/js/main-5d41402abc4b2a76.js"));
+        assert!(!is_fingerprinted("/assets/app.css"));
+        assert!(!is_fingerprinted("/assets/deadbeef.css"));
+        assert!(!is_fingerprinted("/assets/app.abcdefab.css"));
+
+        assert!(is_safe_path("/assets/css/app.css"));
+        assert!(!is_safe_path("/assets/../Cargo.toml"));
+        assert!(!is_safe_path("/assets/%2e%2e/Cargo.toml"));
+        assert!(!is_safe_path("/assets/..%2fCargo.toml"));
+        assert!(!is_safe_path("/assets/..%5cCargo.toml"));
+        assert!(!is_safe_path("/assets/C:/Windows"));
+        assert!(!is_safe_path("/assets/%zz"));
+    }
+
+    #[tokio::test]
+    async fn test_precompressed_assets() {
+        let (server, _directory) = server();
+
+        let response = server
+            .get("/assets/app.3f9a1c2b.css")
+            .add_header("Accept-Encoding", "gzip, br")
+            .await;
+        response.assert_status_ok();
+        response.assert_header("Content-Encoding", "br");
+        response.assert_header("Content-Type", "text/css");
+        response.assert_header("Cache-Control", "public, max-age=31536000, immutable");
+        response.assert_header("Vary", "accept-encoding");
+        assert_eq!(response.text(), "brotli");
+
+        let response = server
+            .get("/assets/app.3f9a1c2b.css")
+            .add_header("Accept-Encoding", "gzip")
+            .await;
+        response.assert_header("Content-Encoding", "gzip");
+        assert_eq!(response.text(), "gzip");
+
+        let response = server.get("/assets/app.3f9a1c2b.css").await;
+        assert!(response.maybe_header("Content-Encoding").is_none());
+        assert_eq!(response.text(), "body { margin: 0 }");
+    }
+
+    #[tokio::test]
+    async fn test_ranges_and_cache() {
+        let (server, _directory) = server();
+
+        let response = server.get("/assets/logo.txt").await;
+        response.assert_status_ok();
+        response.assert_header("Cache-Control", "public, max-age=300");
+        response.assert_header("Accept-Ranges", "bytes");
+
+        let response = server
+            .get("/assets/logo.txt")
+            .add_header("Range", "bytes=2-5")
+            .await;
+        response.assert_status(StatusCode::PARTIAL_CONTENT);
+        response.assert_header("Content-Range", "bytes 2-5/10");
+        assert_eq!(response.text(), "2345");
+
+        server
+            .get("/assets/logo.txt")
+            .add_header("Range", "bytes=20-30")
+            .await
+            .assert_status(StatusCode::RANGE_NOT_SATISFIABLE);
+
+        server
+            .get("/assets/missing.css")
+            .await
+            .assert_status_not_found();
+    }
+
+    #[tokio::test]
+    async fn test_path_traversal() {
+        let (server, _directory) = server();
+
+        for path in [
+            "/assets/..%2fCargo.toml",
+            "/assets/css/..%2f..%2fCargo.toml",
+            "/assets/..%5cCargo.toml",
+        ] {
+            server.get(path).await.assert_status_bad_request();
+        }
+    }
+}
@@ -0,0 +1,377 @@
+use std::{collections::HashMap, time::Duration};
+
+use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use argon2::{
+    PasswordHasher,
+    password_hash::{SaltString, rand_core::OsRng},
+};
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    middleware::{Next, from_fn_with_state},
+    response::Response,
+};
+use http::{HeaderMap, header::AUTHORIZATION, request::Parts};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode, get_current_timestamp,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use utoipa_axum::{router::OpenApiRouter, routes};
+use validator::Validate;
+
+use crate::{
+    app::AppState,
+    error::{AppError, Problem},
+    extract::{Json, ValidatedJson},
+};
+
+#[derive(Serialize, Deserialize, Debug, Validate, ToSchema)]
+pub struct LoginRequest {
+    #[validate(length(
+        min = 3,
+        max = 20,
+        message = "username must be between 3 and 20 characters"
+    ))]
+    #[schema(min_length = 3, max_length = 20)]
+    pub username: String,
+
+    #[validate(length(
+        min = 3,
+        max = 20,
+        message = "password must be between 3 and 20 characters"
+    ))]
+    #[schema(min_length = 3, max_length = 20, format = Password)]
+    pub password: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct LoginResponse {
+    pub token: String,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct User {
+    pub username: String,
+    pub password: String,
+}
+
+pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default().hash_password(password.as_bytes(), &salt)?;
+
+    Ok(hash.to_string())
+}
+
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .and_then(|hash| Argon2::default().verify_password(password.as_bytes(), &hash))
+        .is_ok()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub iat: u64,
+    pub exp: u64,
+}
+
+pub struct TokenService {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    validation: Validation,
+    ttl: Duration,
+}
+
+impl TokenService {
+    pub fn new(secret: &[u8], ttl: Duration) -> Self {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.leeway = 0;
+
+        Self {
+            encoding_key: EncodingKey::from_secret(secret),
+            decoding_key: DecodingKey::from_secret(secret),
+            validation,
+            ttl,
+        }
+    }
+
+    pub fn issue(&self, username: &str) -> Result<String, jsonwebtoken::errors::Error> {
+        let now = get_current_timestamp();
+        let claims = Claims {
+            sub: username.to_string(),
+            iat: now,
+            exp: now + self.ttl.as_secs(),
+        };
+
+        encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key)
+    }
+
+    pub fn verify(&self, token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+        decode::<Claims>(token, &self.decoding_key, &self.validation).map(|data| data.claims)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiKey {
+    pub name: String,
+    pub digest: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ApiKeys {
+    keys: HashMap<String, String>,
+}
+
+impl ApiKeys {
+    pub fn new(keys: impl IntoIterator<Item = ApiKey>) -> Self {
+        Self {
+            keys: keys
+                .into_iter()
+                .map(|key| (key.digest.to_ascii_lowercase(), key.name))
+                .collect(),
+        }
+    }
+
+    pub fn digest(key: &str) -> String {
+        format!("{:x}", Sha256::digest(key.as_bytes()))
+    }
+
+    pub fn verify(&self, key: &str) -> Option<&str> {
+        self.keys.get(&Self::digest(key)).map(String::as_str)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub username: String,
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for AuthUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<AuthUser>()
+            .cloned()
+            .ok_or_else(|| AppError::unauthorized("Unauthorized"))
+    }
+}
+
+pub fn routes(state: AppState) -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(current))
+        .route_layer(from_fn_with_state(state, auth_middleware))
+        .routes(routes!(login))
+}
+
+#[utoipa::path(
+    post,
+    path = "/login",
+    tag = "users",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, body = LoginResponse),
+        (status = 401, description = "Username or password is wrong", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Request body is invalid", body = Problem, content_type = "application/problem+json")
+    )
+)]
+async fn login(
+    State(state): State<AppState>,
+    ValidatedJson(request): ValidatedJson<LoginRequest>,
+) -> Result<Json<LoginResponse>, AppError> {
+    let user = state.users.find_by_username(&request.username).await?;
+
+    match user {
+        Some(user) if verify_password(&request.password, &user.password) => {
+            let token = state
+                .tokens
+                .issue(&user.username)
+                .map_err(|err| AppError::internal(format!("Failed to issue token : {}", err)))?;
+
+            Ok(Json(LoginResponse { token }))
+        }
+        _ => Err(AppError::unauthorized("Username or password is wrong")),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/current",
+    tag = "users",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, body = String, content_type = "text/plain"),
+        (status = 401, description = "Bearer token is missing or invalid", body = Problem, content_type = "application/problem+json")
+    )
+)]
+async fn current(user: AuthUser) -> String {
+    format!("Hello {}", user.username)
+}
+
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+pub async fn auth_middleware(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let token = bearer_token(request.headers())
+        .ok_or_else(|| AppError::unauthorized("Missing bearer token"))?;
+
+    let claims = state
+        .tokens
+        .verify(token)
+        .map_err(|_| AppError::unauthorized("Invalid or expired token"))?;
+
+    request.extensions_mut().insert(AuthUser {
+        username: claims.sub,
+    });
+
+    Ok(next.run(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use axum_test::TestServer;
+    use jsonwebtoken::{EncodingKey, Header, encode, get_current_timestamp};
+
+    use super::{Claims, LoginRequest, LoginResponse, TokenService};
+    use crate::{
+        app::{AppState, router},
+        error::Problem,
+        repository::MemoryRepository,
+    };
+
+    const SECRET: &[u8] = b"secret";
+
+    fn server() -> TestServer {
+        let repository = Arc::new(MemoryRepository::new());
+        repository.add_user("rizki", "rahasia");
+
+        let state = AppState::new(
+            repository,
+            TokenService::new(SECRET, Duration::from_secs(60)),
+        );
+        TestServer::new(router(state)).unwrap()
+    }
+
+    async fn login(server: &TestServer, username: &str, password: &str) -> axum_test::TestResponse {
+        server
+            .post("/api/users/login")
+            .json(&LoginRequest {
+                username: username.to_string(),
+                password: password.to_string(),
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_login_success() {
+        let server = server();
+
+        let response = login(&server, "rizki", "rahasia").await;
+        response.assert_status_ok();
+        let LoginResponse { token } = response.json();
+
+        let response = server
+            .get("/api/users/current")
+            .authorization_bearer(token)
+            .await;
+        response.assert_status_ok();
+        response.assert_text("Hello rizki");
+    }
+
+    #[tokio::test]
+    async fn test_login_failed() {
+        let server = server();
+
+        let response = login(&server, "rizki", "salah").await;
+        response.assert_status_unauthorized();
+        assert_eq!(
+            response.json::<Problem>().detail,
+            "Username or password is wrong"
+        );
+
+        let response = login(&server, "budi", "rahasia").await;
+        response.assert_status_unauthorized();
+        assert_eq!(
+            response.json::<Problem>().detail,
+            "Username or password is wrong"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_token() {
+        let server = server();
+
+        let response = server.get("/api/users/current").await;
+        response.assert_status_unauthorized();
+        assert_eq!(response.json::<Problem>().detail, "Missing bearer token");
+    }
+
+    #[tokio::test]
+    async fn test_tampered_token() {
+        let server = server();
+
+        let LoginResponse { token } = login(&server, "rizki", "rahasia").await.json();
+        let forged = TokenService::new(b"other-secret", Duration::from_secs(60))
+            .issue("admin")
+            .unwrap();
+        let parts: Vec<&str> = token.split('.').collect();
+        let forged_parts: Vec<&str> = forged.split('.').collect();
+
+        let response = server
+            .get("/api/users/current")
+            .authorization_bearer(format!("{}.{}.{}", parts[0], forged_parts[1], parts[2]))
+            .await;
+        response.assert_status_unauthorized();
+        assert_eq!(
+            response.json::<Problem>().detail,
+            "Invalid or expired token"
+        );
+
+        let response = server
+            .get("/api/users/current")
+            .authorization_bearer(forged)
+            .await;
+        response.assert_status_unauthorized();
+    }
+
+    #[tokio::test]
+    async fn test_expired_token() {
+        let server = server();
+
+        let now = get_current_timestamp();
+        let claims = Claims {
+            sub: "rizki".to_string(),
+            iat: now - 120,
+            exp: now - 60,
+        };
+        let token = encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(SECRET),
+        )
+        .unwrap();
+
+        let response = server
+            .get("/api/users/current")
+            .authorization_bearer(token)
+            .await;
+        response.assert_status_unauthorized();
+        assert_eq!(
+            response.json::<Problem>().detail,
+            "Invalid or expired token"
+        );
+    }
+}
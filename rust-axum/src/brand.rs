@@ -0,0 +1,393 @@
+use axum::{
+    Router,
+    extract::{Path, State},
+    routing::get,
+};
+use http::{HeaderName, StatusCode, header::LOCATION};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{
+    app::AppState,
+    conditional::{Conditional, Preconditions, Versioned, precondition_failed},
+    error::{AppError, Problem, validate_list},
+    extract::Json,
+    jobs::{JobAccepted, JobHandle},
+    live::{Event, EventAction},
+    model::{Brand, BrandRequest},
+    negotiate::{Accept, AcceptStructured, Negotiated, ValidatedNegotiated},
+    pagination::{ListQuery, Page},
+    shutdown::ShutdownSignal,
+    timeout::Deadline,
+    view::{ErrorPage, Listing, View},
+};
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(list, create))
+        .routes(routes!(find, update, delete))
+        .routes(routes!(import))
+}
+
+pub fn pages() -> Router<AppState> {
+    Router::new().route("/brands", get(page))
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "brands",
+    params(ListQuery<Brand>),
+    responses(
+        (status = 200, body = Page<Brand>),
+        (status = 400, description = "Query parameters are invalid", body = Problem, content_type = "application/problem+json"),
+        (status = 406, description = "Accept does not allow a supported format", body = Problem, content_type = "application/problem+json"),
+        (status = 503, description = "Listing did not finish within the request deadline", body = Problem, content_type = "application/problem+json")
+    )
+)]
+async fn list(
+    State(state): State<AppState>,
+    AcceptStructured(format): AcceptStructured,
+    deadline: Deadline,
+    query: ListQuery<Brand>,
+) -> Result<Negotiated<Page<Brand>>, AppError> {
+    let (items, total) = deadline.run(state.brands.find_page(&query)).await??;
+    Ok(Negotiated(format, query.into_page(items, total)))
+}
+
+async fn page(
+    State(state): State<AppState>,
+    query: Result<ListQuery<Brand>, AppError>,
+) -> Result<View, ErrorPage> {
+    let query = query.map_err(|err| state.templates.error(err))?;
+    let (items, total) = state
+        .brands
+        .find_page(&query)
+        .await
+        .map_err(|err| state.templates.error(err))?;
+
+    Ok(state.templates.view(
+        "brands",
+        &Listing {
+            title: "Brands",
+            page: query.into_page(items, total),
+        },
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "brands",
+    params(
+        ("id" = i32, Path, description = "Brand id"),
+        ("If-None-Match" = Option<String>, Header, description = "Entity tags of a cached copy")
+    ),
+    responses(
+        (status = 200, body = Brand),
+        (status = 304, description = "Brand has not been modified"),
+        (status = 404, description = "Brand is not found", body = Problem, content_type = "application/problem+json"),
+        (status = 406, description = "Accept does not allow a supported format", body = Problem, content_type = "application/problem+json")
+    )
+)]
+async fn find(
+    State(state): State<AppState>,
+    Accept(format): Accept,
+    Path(id): Path<i32>,
+) -> Result<Conditional<Brand>, AppError> {
+    state
+        .brands
+        .find_by_id(id)
+        .await?
+        .map(|value| Conditional(format, value))
+        .ok_or_else(|| AppError::not_found(format!("Brand {} is not found", id)))
+}
+
+async fn conflict(state: &AppState, id: i32) -> AppError {
+    match current(state, id).await {
+        Ok(brand) => precondition_failed(&brand.validator()),
+        Err(err) => err,
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "brands",
+    request_body = BrandRequest,
+    responses(
+        (status = 201, body = Brand),
+        (status = 406, description = "Accept does not allow a supported format", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Request body is invalid", body = Problem, content_type = "application/problem+json")
+    )
+)]
+async fn create(
+    State(state): State<AppState>,
+    Accept(format): Accept,
+    ValidatedNegotiated(request): ValidatedNegotiated<BrandRequest>,
+) -> Result<(StatusCode, Negotiated<Brand>), AppError> {
+    let brand = state.brands.create(request).await?;
+    state
+        .events
+        .publish(Event::new("brand", brand.id, EventAction::Created, &brand));
+
+    Ok((StatusCode::CREATED, Negotiated(format, brand)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/import",
+    tag = "brands",
+    request_body = Vec<BrandRequest>,
+    responses(
+        (status = 202, description = "Import is running, follow the events link for progress", body = JobAccepted),
+        (status = 422, description = "Request body is invalid", body = Problem, content_type = "application/problem+json")
+    )
+)]
+async fn import(
+    State(state): State<AppState>,
+    Json(requests): Json<Vec<BrandRequest>>,
+) -> Result<(StatusCode, [(HeaderName, String); 1], Json<JobAccepted>), AppError> {
+    validate_list(&requests)?;
+
+    let (id, job) = state.jobs.create();
+    let events = format!("/api/jobs/{}/events", id);
+    let shutdown = state.shutdown.signal();
+    tokio::spawn(run_import(state, job, requests, shutdown));
+
+    Ok((
+        StatusCode::ACCEPTED,
+        [(LOCATION, events.clone())],
+        Json(JobAccepted { id, events }),
+    ))
+}
+
+async fn run_import(
+    state: AppState,
+    job: JobHandle,
+    requests: Vec<BrandRequest>,
+    shutdown: ShutdownSignal,
+) {
+    let total = requests.len();
+
+    for (index, request) in requests.into_iter().enumerate() {
+        let progress = (index * 100 / total.max(1)) as u8;
+
+        if shutdown.is_shutdown() {
+            return job.fail(
+                progress,
+                format!(
+                    "Import stopped by shutdown after {} of {} brands",
+                    index, total
+                ),
+            );
+        }
+
+        match state.brands.create(request).await {
+            Ok(brand) => {
+                job.progress(
+                    ((index + 1) * 100 / total) as u8,
+                    format!("Imported brand {}", brand.name),
+                );
+                state
+                    .events
+                    .publish(Event::new("brand", brand.id, EventAction::Created, &brand));
+            }
+            Err(err) => {
+                eprintln!("Brand import failed : {}", err);
+                return job.fail(progress, format!("Failed to import brand {}", index + 1));
+            }
+        }
+    }
+
+    job.complete(format!("Imported {} brands", total));
+}
+
+async fn current(state: &AppState, id: i32) -> Result<Brand, AppError> {
+    state
+        .brands
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| AppError::not_found(format!("Brand {} is not found", id)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/{id}",
+    tag = "brands",
+    params(
+        ("id" = i32, Path, description = "Brand id"),
+        ("If-Match" = Option<String>, Header, description = "Entity tag the update is based on")
+    ),
+    request_body = BrandRequest,
+    responses(
+        (status = 200, body = Brand),
+        (status = 404, description = "Brand is not found", body = Problem, content_type = "application/problem+json"),
+        (status = 406, description = "Accept does not allow a supported format", body = Problem, content_type = "application/problem+json"),
+        (status = 412, description = "Brand has been modified by someone else", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Request body is invalid", body = Problem, content_type = "application/problem+json")
+    )
+)]
+async fn update(
+    State(state): State<AppState>,
+    Accept(format): Accept,
+    Path(id): Path<i32>,
+    preconditions: Preconditions,
+    ValidatedNegotiated(request): ValidatedNegotiated<BrandRequest>,
+) -> Result<Conditional<Brand>, AppError> {
+    let updated = if preconditions.is_empty() {
+        state.brands.update(id, request).await?
+    } else {
+        let brand = current(&state, id).await?;
+        preconditions.check(&brand.validator())?;
+        state.brands.update_if(&brand, request).await?
+    };
+    let Some(brand) = updated else {
+        return Err(conflict(&state, id).await);
+    };
+    state
+        .events
+        .publish(Event::new("brand", id, EventAction::Updated, &brand));
+
+    Ok(Conditional(format, brand))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = "brands",
+    params(
+        ("id" = i32, Path, description = "Brand id"),
+        ("If-Match" = Option<String>, Header, description = "Entity tag the deletion is based on")
+    ),
+    responses(
+        (status = 204, description = "Brand is deleted"),
+        (status = 404, description = "Brand is not found", body = Problem, content_type = "application/problem+json"),
+        (status = 412, description = "Brand has been modified by someone else", body = Problem, content_type = "application/problem+json")
+    )
+)]
+async fn delete(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    preconditions: Preconditions,
+) -> Result<StatusCode, AppError> {
+    let brand = current(&state, id).await?;
+    preconditions.check(&brand.validator())?;
+
+    let deleted = if preconditions.is_empty() {
+        state.brands.delete(id).await?
+    } else {
+        state.brands.delete_if(&brand).await?
+    };
+
+    if deleted {
+        state
+            .events
+            .publish(Event::new("brand", id, EventAction::Deleted, &brand));
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(conflict(&state, id).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use axum_test::TestServer;
+    use http::StatusCode;
+
+    use crate::{
+        app::{AppState, router},
+        auth::TokenService,
+        model::{Brand, BrandRequest},
+        pagination::Page,
+        repository::MemoryRepository,
+    };
+
+    fn server() -> TestServer {
+        let state = AppState::new(
+            Arc::new(MemoryRepository::new()),
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        );
+        TestServer::new(router(state)).unwrap()
+    }
+
+    fn request(name: &str) -> BrandRequest {
+        BrandRequest {
+            name: name.to_string(),
+            description: Some(format!("{} description", name)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_and_list_brands() {
+        let server = server();
+
+        let response = server.post("/api/brands").json(&request("Samsung")).await;
+        response.assert_status(StatusCode::CREATED);
+        let brand: Brand = response.json();
+        assert_eq!(brand.id, 1);
+        assert_eq!(brand.name, "Samsung");
+
+        server.post("/api/brands").json(&request("Apple")).await;
+
+        let response = server.get("/api/brands").await;
+        response.assert_status_ok();
+        let brands: Page<Brand> = response.json();
+        assert_eq!(brands.total, 2);
+        let brands = brands.items;
+        assert_eq!(brands.len(), 2);
+        assert_eq!(brands[1].name, "Apple");
+    }
+
+    #[tokio::test]
+    async fn test_get_brand() {
+        let server = server();
+        server.post("/api/brands").json(&request("Samsung")).await;
+
+        let response = server.get("/api/brands/1").await;
+        response.assert_status_ok();
+        let brand: Brand = response.json();
+        assert_eq!(brand.id, 1);
+        assert_eq!(brand.name, "Samsung");
+        assert_eq!(brand.description, Some("Samsung description".to_string()));
+
+        let response = server.get("/api/brands/2").await;
+        response.assert_status_not_found();
+    }
+
+    #[tokio::test]
+    async fn test_update_brand() {
+        let server = server();
+        let created: Brand = server
+            .post("/api/brands")
+            .json(&request("Samsung"))
+            .await
+            .json();
+
+        let response = server.put("/api/brands/1").json(&request("Xiaomi")).await;
+        response.assert_status_ok();
+        let brand: Brand = response.json();
+        assert_eq!(brand.name, "Xiaomi");
+        assert_eq!(brand.created_at, created.created_at);
+        assert!(brand.updated_at >= created.updated_at);
+
+        let response = server.put("/api/brands/2").json(&request("Xiaomi")).await;
+        response.assert_status_not_found();
+    }
+
+    #[tokio::test]
+    async fn test_delete_brand() {
+        let server = server();
+        server.post("/api/brands").json(&request("Samsung")).await;
+
+        let response = server.delete("/api/brands/1").await;
+        response.assert_status(StatusCode::NO_CONTENT);
+
+        let response = server.get("/api/brands/1").await;
+        response.assert_status_not_found();
+
+        let response = server.delete("/api/brands/1").await;
+        response.assert_status_not_found();
+    }
+}
@@ -0,0 +1,393 @@
+use axum::{
+    Router,
+    extract::{Path, State},
+    routing::get,
+};
+use http::StatusCode;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{
+    app::AppState,
+    conditional::{Conditional, Preconditions, Versioned, precondition_failed},
+    error::{AppError, Problem},
+    live::{Event, EventAction},
+    model::{Category, CategoryRequest},
+    negotiate::{Accept, AcceptStructured, Negotiated, ValidatedNegotiated},
+    pagination::{ListQuery, Page},
+    timeout::Deadline,
+    view::{ErrorPage, Listing, View},
+};
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(list, create))
+        .routes(routes!(find, update, delete))
+}
+
+pub fn pages() -> Router<AppState> {
+    Router::new().route("/categories", get(page))
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "categories",
+    params(ListQuery<Category>),
+    responses(
+        (status = 200, body = Page<Category>),
+        (status = 400, description = "Query parameters are invalid", body = Problem, content_type = "application/problem+json"),
+        (status = 406, description = "Accept does not allow a supported format", body = Problem, content_type = "application/problem+json"),
+        (status = 503, description = "Listing did not finish within the request deadline", body = Problem, content_type = "application/problem+json")
+    )
+)]
+async fn list(
+    State(state): State<AppState>,
+    AcceptStructured(format): AcceptStructured,
+    deadline: Deadline,
+    query: ListQuery<Category>,
+) -> Result<Negotiated<Page<Category>>, AppError> {
+    let (items, total) = deadline.run(state.categories.find_page(&query)).await??;
+    Ok(Negotiated(format, query.into_page(items, total)))
+}
+
+async fn page(
+    State(state): State<AppState>,
+    query: Result<ListQuery<Category>, AppError>,
+) -> Result<View, ErrorPage> {
+    let query = query.map_err(|err| state.templates.error(err))?;
+    let (items, total) = state
+        .categories
+        .find_page(&query)
+        .await
+        .map_err(|err| state.templates.error(err))?;
+
+    Ok(state.templates.view(
+        "categories",
+        &Listing {
+            title: "Categories",
+            page: query.into_page(items, total),
+        },
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "categories",
+    params(
+        ("id" = i32, Path, description = "Category id"),
+        ("If-None-Match" = Option<String>, Header, description = "Entity tags of a cached copy")
+    ),
+    responses(
+        (status = 200, body = Category),
+        (status = 304, description = "Category has not been modified"),
+        (status = 404, description = "Category is not found", body = Problem, content_type = "application/problem+json"),
+        (status = 406, description = "Accept does not allow a supported format", body = Problem, content_type = "application/problem+json")
+    )
+)]
+async fn find(
+    State(state): State<AppState>,
+    Accept(format): Accept,
+    Path(id): Path<i32>,
+) -> Result<Conditional<Category>, AppError> {
+    state
+        .categories
+        .find_by_id(id)
+        .await?
+        .map(|value| Conditional(format, value))
+        .ok_or_else(|| AppError::not_found(format!("Category {} is not found", id)))
+}
+
+async fn conflict(state: &AppState, id: i32) -> AppError {
+    match current(state, id).await {
+        Ok(category) => precondition_failed(&category.validator()),
+        Err(err) => err,
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "categories",
+    request_body = CategoryRequest,
+    responses(
+        (status = 201, body = Category),
+        (status = 406, description = "Accept does not allow a supported format", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Request body is invalid", body = Problem, content_type = "application/problem+json")
+    )
+)]
+async fn create(
+    State(state): State<AppState>,
+    Accept(format): Accept,
+    ValidatedNegotiated(request): ValidatedNegotiated<CategoryRequest>,
+) -> Result<(StatusCode, Negotiated<Category>), AppError> {
+    let category = state.categories.create(request).await?;
+    state.events.publish(Event::new(
+        "category",
+        category.id,
+        EventAction::Created,
+        &category,
+    ));
+
+    Ok((StatusCode::CREATED, Negotiated(format, category)))
+}
+
+async fn current(state: &AppState, id: i32) -> Result<Category, AppError> {
+    state
+        .categories
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| AppError::not_found(format!("Category {} is not found", id)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/{id}",
+    tag = "categories",
+    params(
+        ("id" = i32, Path, description = "Category id"),
+        ("If-Match" = Option<String>, Header, description = "Entity tag the update is based on")
+    ),
+    request_body = CategoryRequest,
+    responses(
+        (status = 200, body = Category),
+        (status = 404, description = "Category is not found", body = Problem, content_type = "application/problem+json"),
+        (status = 406, description = "Accept does not allow a supported format", body = Problem, content_type = "application/problem+json"),
+        (status = 412, description = "Category has been modified by someone else", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Request body is invalid", body = Problem, content_type = "application/problem+json")
+    )
+)]
+async fn update(
+    State(state): State<AppState>,
+    Accept(format): Accept,
+    Path(id): Path<i32>,
+    preconditions: Preconditions,
+    ValidatedNegotiated(request): ValidatedNegotiated<CategoryRequest>,
+) -> Result<Conditional<Category>, AppError> {
+    let updated = if preconditions.is_empty() {
+        state.categories.update(id, request).await?
+    } else {
+        let category = current(&state, id).await?;
+        preconditions.check(&category.validator())?;
+        state.categories.update_if(&category, request).await?
+    };
+    let Some(category) = updated else {
+        return Err(conflict(&state, id).await);
+    };
+    state
+        .events
+        .publish(Event::new("category", id, EventAction::Updated, &category));
+
+    Ok(Conditional(format, category))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    tag = "categories",
+    params(
+        ("id" = i32, Path, description = "Category id"),
+        ("If-Match" = Option<String>, Header, description = "Entity tag the deletion is based on")
+    ),
+    responses(
+        (status = 204, description = "Category is deleted"),
+        (status = 404, description = "Category is not found", body = Problem, content_type = "application/problem+json"),
+        (status = 412, description = "Category has been modified by someone else", body = Problem, content_type = "application/problem+json")
+    )
+)]
+async fn delete(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    preconditions: Preconditions,
+) -> Result<StatusCode, AppError> {
+    let category = current(&state, id).await?;
+    preconditions.check(&category.validator())?;
+
+    let deleted = if preconditions.is_empty() {
+        state.categories.delete(id).await?
+    } else {
+        state.categories.delete_if(&category).await?
+    };
+
+    if deleted {
+        state
+            .events
+            .publish(Event::new("category", id, EventAction::Deleted, &category));
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(conflict(&state, id).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use axum_test::TestServer;
+    use http::StatusCode;
+
+    use crate::{
+        app::{AppState, router},
+        auth::TokenService,
+        error::Problem,
+        model::{Category, CategoryRequest},
+        pagination::Page,
+        repository::MemoryRepository,
+    };
+
+    fn server() -> TestServer {
+        let state = AppState::new(
+            Arc::new(MemoryRepository::new()),
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        );
+        TestServer::new(router(state)).unwrap()
+    }
+
+    fn request(name: &str) -> CategoryRequest {
+        CategoryRequest {
+            name: name.to_string(),
+            description: Some(format!("{} description", name)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_and_list_categories() {
+        let server = server();
+
+        let response = server
+            .post("/api/categories")
+            .json(&request("Gadget"))
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        let category: Category = response.json();
+        assert_eq!(category.id, 1);
+        assert_eq!(category.name, "Gadget");
+
+        server
+            .post("/api/categories")
+            .json(&request("Fashion"))
+            .await;
+
+        let response = server.get("/api/categories").await;
+        response.assert_status_ok();
+        let categories: Page<Category> = response.json();
+        assert_eq!(categories.total, 2);
+        assert_eq!(categories.items.len(), 2);
+        assert_eq!(categories.items[1].name, "Fashion");
+    }
+
+    #[tokio::test]
+    async fn test_list_categories_with_query() {
+        let server = server();
+        for name in ["Gadget", "Fashion", "Food", "Furniture", "Book"] {
+            server.post("/api/categories").json(&request(name)).await;
+        }
+
+        let response = server
+            .get("/api/categories?name__startswith=F&sort=-name&per_page=2")
+            .await;
+        response.assert_status_ok();
+        let page: Page<Category> = response.json();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.page, Some(1));
+        let names: Vec<&str> = page.items.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Furniture", "Food"]);
+        assert_eq!(page.prev, None);
+        let next = page.next.unwrap();
+        assert_eq!(
+            next,
+            "/api/categories?name__startswith=F&sort=-name&per_page=2&page=2"
+        );
+
+        let page: Page<Category> = server.get(&next).await.json();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].name, "Fashion");
+        assert_eq!(page.next, None);
+        assert_eq!(
+            page.prev.as_deref(),
+            Some("/api/categories?name__startswith=F&sort=-name&per_page=2&page=1")
+        );
+
+        let page: Page<Category> = server
+            .get("/api/categories?after=2&per_page=2")
+            .await
+            .json();
+        let ids: Vec<i32> = page.items.iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec![3, 4]);
+        assert_eq!(page.page, None);
+        assert_eq!(
+            page.next.as_deref(),
+            Some("/api/categories?per_page=2&after=4")
+        );
+
+        let response = server.get("/api/categories?sort=description").await;
+        response.assert_status_bad_request();
+        let problem: Problem = response.json();
+        assert_eq!(problem.extensions["parameter"], "sort");
+    }
+
+    #[tokio::test]
+    async fn test_create_invalid_category() {
+        let server = server();
+
+        let response = server.post("/api/categories").json(&request("  ")).await;
+        response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        let problem: Problem = response.json();
+        assert_eq!(problem.extensions["errors"]["name"][0]["code"], "not_blank");
+    }
+
+    #[tokio::test]
+    async fn test_get_category() {
+        let server = server();
+        server
+            .post("/api/categories")
+            .json(&request("Gadget"))
+            .await;
+
+        let response = server.get("/api/categories/1").await;
+        response.assert_status_ok();
+        response.assert_json(&Category {
+            id: 1,
+            name: "Gadget".to_string(),
+            description: Some("Gadget description".to_string()),
+        });
+
+        let response = server.get("/api/categories/2").await;
+        response.assert_status_not_found();
+    }
+
+    #[tokio::test]
+    async fn test_update_category() {
+        let server = server();
+        server
+            .post("/api/categories")
+            .json(&request("Gadget"))
+            .await;
+
+        let response = server.put("/api/categories/1").json(&request("Food")).await;
+        response.assert_status_ok();
+        let category: Category = response.json();
+        assert_eq!(category.name, "Food");
+
+        let response = server.put("/api/categories/2").json(&request("Food")).await;
+        response.assert_status_not_found();
+    }
+
+    #[tokio::test]
+    async fn test_delete_category() {
+        let server = server();
+        server
+            .post("/api/categories")
+            .json(&request("Gadget"))
+            .await;
+
+        let response = server.delete("/api/categories/1").await;
+        response.assert_status(StatusCode::NO_CONTENT);
+
+        let response = server.get("/api/categories/1").await;
+        response.assert_status_not_found();
+
+        let response = server.delete("/api/categories/1").await;
+        response.assert_status_not_found();
+    }
+}
@@ -0,0 +1,573 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::{FromRequestParts, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, NaiveDateTime, Timelike};
+use http::{
+    HeaderMap, HeaderValue, Method, StatusCode,
+    header::{
+        CACHE_CONTROL, ETAG, IF_MATCH, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_UNMODIFIED_SINCE,
+        LAST_MODIFIED, VARY,
+    },
+    request::Parts,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::AppError,
+    model::{Brand, Category},
+    negotiate::{Format, Negotiated},
+};
+
+const HTTP_DATE: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Validator {
+    pub etag: String,
+    pub last_modified: Option<NaiveDateTime>,
+}
+
+impl Validator {
+    pub fn for_content<T: Serialize>(value: &T) -> Self {
+        let bytes = serde_json::to_vec(value).unwrap_or_default();
+        let digest = format!("{:x}", Sha256::digest(&bytes));
+
+        Self {
+            etag: format!("\"{}\"", &digest[..32]),
+            last_modified: None,
+        }
+    }
+
+    /// Each representation needs its own strong entity tag, so non-JSON
+    /// formats mix their media type into the digest.
+    pub fn represented_as(&self, format: Format) -> Self {
+        if format == Format::Json {
+            return self.clone();
+        }
+
+        let mut digest = Sha256::new();
+        digest.update(self.etag.as_bytes());
+        digest.update([0]);
+        digest.update(format.media_type());
+        let digest = format!("{:x}", digest.finalize());
+
+        Self {
+            etag: format!("\"{}\"", &digest[..32]),
+            last_modified: self.last_modified,
+        }
+    }
+
+    pub fn with_last_modified(mut self, last_modified: NaiveDateTime) -> Self {
+        self.last_modified = last_modified.with_nanosecond(0);
+        self
+    }
+
+    fn insert_headers(&self, headers: &mut HeaderMap) {
+        if let Ok(etag) = HeaderValue::from_str(&self.etag) {
+            headers.insert(ETAG, etag);
+        }
+        if let Some(last_modified) = self.last_modified
+            && let Ok(value) = HeaderValue::from_str(&format_http_date(last_modified))
+        {
+            headers.insert(LAST_MODIFIED, value);
+        }
+    }
+}
+
+pub trait Versioned {
+    fn validator(&self) -> Validator;
+}
+
+impl Versioned for Brand {
+    fn validator(&self) -> Validator {
+        Validator::for_content(self).with_last_modified(self.updated_at)
+    }
+}
+
+impl Versioned for Category {
+    fn validator(&self) -> Validator {
+        Validator::for_content(self)
+    }
+}
+
+pub fn format_http_date(value: NaiveDateTime) -> String {
+    value.format(HTTP_DATE).to_string()
+}
+
+pub fn parse_http_date(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value.trim(), HTTP_DATE)
+        .ok()
+        .or_else(|| {
+            DateTime::parse_from_rfc2822(value.trim())
+                .ok()
+                .map(|date| date.naive_utc())
+        })
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &http::HeaderName) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+fn entity_tags(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+}
+
+fn weak_match(list: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim_start_matches("W/").to_string();
+    entity_tags(list).any(|tag| tag == "*" || opaque(tag) == opaque(etag))
+}
+
+fn strong_match(list: &str, etag: &str) -> bool {
+    entity_tags(list)
+        .any(|tag| tag == "*" || (!tag.starts_with("W/") && !etag.starts_with("W/") && tag == etag))
+}
+
+pub struct Conditional<T>(pub Format, pub T);
+
+impl<T> IntoResponse for Conditional<T>
+where
+    T: Versioned + Serialize,
+{
+    fn into_response(self) -> Response {
+        let Conditional(format, value) = self;
+        let validator = value.validator().represented_as(format);
+        let mut response = Negotiated(format, value).into_response();
+        if !response.status().is_success() {
+            return response;
+        }
+        validator.insert_headers(response.headers_mut());
+
+        response
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Preconditions {
+    pub if_match: Option<String>,
+    pub if_unmodified_since: Option<NaiveDateTime>,
+}
+
+pub fn precondition_failed(current: &Validator) -> AppError {
+    AppError::new(
+        StatusCode::PRECONDITION_FAILED,
+        "Resource has been modified since it was last fetched",
+    )
+    .with_extension("etag", current.etag.clone())
+}
+
+impl Preconditions {
+    pub fn is_empty(&self) -> bool {
+        self.if_match.is_none() && self.if_unmodified_since.is_none()
+    }
+
+    pub fn check(&self, current: &Validator) -> Result<(), AppError> {
+        let matched = match (&self.if_match, self.if_unmodified_since) {
+            (Some(if_match), _) => Format::ALL
+                .into_iter()
+                .any(|format| strong_match(if_match, &current.represented_as(format).etag)),
+            (None, Some(since)) => current
+                .last_modified
+                .is_some_and(|last_modified| last_modified <= since),
+            (None, None) => true,
+        };
+
+        if matched {
+            Ok(())
+        } else {
+            Err(precondition_failed(current))
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Preconditions {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            if_match: header_str(&parts.headers, &IF_MATCH).map(str::to_string),
+            if_unmodified_since: header_str(&parts.headers, &IF_UNMODIFIED_SINCE)
+                .and_then(parse_http_date),
+        })
+    }
+}
+
+struct Conditions {
+    if_none_match: Option<String>,
+    if_modified_since: Option<NaiveDateTime>,
+}
+
+impl Conditions {
+    fn from_request(request: &Request) -> Option<Self> {
+        if !matches!(*request.method(), Method::GET | Method::HEAD) {
+            return None;
+        }
+
+        let conditions = Self {
+            if_none_match: header_str(request.headers(), &IF_NONE_MATCH).map(str::to_string),
+            if_modified_since: header_str(request.headers(), &IF_MODIFIED_SINCE)
+                .and_then(parse_http_date),
+        };
+        (conditions.if_none_match.is_some() || conditions.if_modified_since.is_some())
+            .then_some(conditions)
+    }
+
+    fn not_modified(&self, response: &HeaderMap) -> bool {
+        if let Some(if_none_match) = &self.if_none_match {
+            return header_str(response, &ETAG).is_some_and(|etag| weak_match(if_none_match, etag));
+        }
+
+        let last_modified = header_str(response, &LAST_MODIFIED).and_then(parse_http_date);
+        matches!(
+            (self.if_modified_since, last_modified),
+            (Some(since), Some(last_modified)) if last_modified <= since
+        )
+    }
+}
+
+pub async fn conditional_middleware(request: Request, next: Next) -> Response {
+    let conditions = Conditions::from_request(&request);
+    let response = next.run(request).await;
+
+    match conditions {
+        Some(conditions)
+            if response.status() == StatusCode::OK
+                && conditions.not_modified(response.headers()) =>
+        {
+            let mut not_modified = StatusCode::NOT_MODIFIED.into_response();
+            for name in [ETAG, LAST_MODIFIED, CACHE_CONTROL, VARY] {
+                if let Some(value) = response.headers().get(&name) {
+                    not_modified.headers_mut().insert(name, value.clone());
+                }
+            }
+            not_modified
+        }
+        _ => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+        time::Duration,
+    };
+
+    use async_trait::async_trait;
+    use axum_test::TestServer;
+    use chrono::{FixedOffset, NaiveDate, TimeZone, Utc};
+    use http::StatusCode;
+    use sqlx::Error;
+
+    use super::{Versioned, format_http_date, parse_http_date, strong_match, weak_match};
+    use crate::{
+        app::{AppState, router},
+        auth::TokenService,
+        error::Problem,
+        model::{Brand, BrandRequest},
+        pagination::ListQuery,
+        repository::{BrandRepository, MemoryRepository},
+    };
+
+    /// Lets another writer update the brand right after the handler has read it.
+    struct Racing {
+        inner: Arc<MemoryRepository>,
+        armed: AtomicBool,
+    }
+
+    #[async_trait]
+    impl BrandRepository for Racing {
+        async fn find_all(&self) -> Result<Vec<Brand>, Error> {
+            BrandRepository::find_all(&*self.inner).await
+        }
+
+        async fn find_page(&self, query: &ListQuery<Brand>) -> Result<(Vec<Brand>, i64), Error> {
+            BrandRepository::find_page(&*self.inner, query).await
+        }
+
+        async fn find_by_id(&self, id: i32) -> Result<Option<Brand>, Error> {
+            let brand = BrandRepository::find_by_id(&*self.inner, id).await?;
+            if self.armed.swap(false, Ordering::SeqCst) {
+                BrandRepository::update(&*self.inner, id, request("Vivo")).await?;
+            }
+            Ok(brand)
+        }
+
+        async fn create(&self, request: BrandRequest) -> Result<Brand, Error> {
+            BrandRepository::create(&*self.inner, request).await
+        }
+
+        async fn update(&self, id: i32, request: BrandRequest) -> Result<Option<Brand>, Error> {
+            BrandRepository::update(&*self.inner, id, request).await
+        }
+
+        async fn update_if(
+            &self,
+            expected: &Brand,
+            request: BrandRequest,
+        ) -> Result<Option<Brand>, Error> {
+            self.inner.update_if(expected, request).await
+        }
+
+        async fn delete(&self, id: i32) -> Result<bool, Error> {
+            BrandRepository::delete(&*self.inner, id).await
+        }
+
+        async fn delete_if(&self, expected: &Brand) -> Result<bool, Error> {
+            self.inner.delete_if(expected).await
+        }
+    }
+
+    async fn server() -> TestServer {
+        let state = AppState::new(
+            Arc::new(MemoryRepository::new()),
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        );
+        let server = TestServer::new(router(state)).unwrap();
+        server.post("/api/brands").json(&request("Samsung")).await;
+
+        server
+    }
+
+    fn request(name: &str) -> BrandRequest {
+        BrandRequest {
+            name: name.to_string(),
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_http_date_and_entity_tags() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 16)
+            .unwrap()
+            .and_hms_opt(8, 0, 0)
+            .unwrap();
+        assert_eq!(format_http_date(date), "Sun, 16 Mar 2025 08:00:00 GMT");
+        assert_eq!(parse_http_date("Sun, 16 Mar 2025 08:00:00 GMT"), Some(date));
+        assert_eq!(parse_http_date("kemarin"), None);
+
+        assert!(weak_match(r#""a", W/"b""#, r#""b""#));
+        assert!(weak_match("*", r#""b""#));
+        assert!(!weak_match(r#""a""#, r#""b""#));
+        assert!(strong_match(r#""a", "b""#, r#""b""#));
+        assert!(!strong_match(r#"W/"b""#, r#""b""#));
+    }
+
+    #[tokio::test]
+    async fn test_conditional_get() {
+        let server = server().await;
+
+        let response = server.get("/api/brands/1").await;
+        response.assert_status_ok();
+        let etag = response.header("ETag").to_str().unwrap().to_string();
+        let last_modified = response
+            .header("Last-Modified")
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let response = server
+            .get("/api/brands/1")
+            .add_header("If-None-Match", &etag)
+            .await;
+        response.assert_status(StatusCode::NOT_MODIFIED);
+        response.assert_header("ETag", &etag);
+        assert!(response.as_bytes().is_empty());
+
+        server
+            .get("/api/brands/1")
+            .add_header("If-None-Match", format!("\"lama\", W/{}", etag))
+            .await
+            .assert_status(StatusCode::NOT_MODIFIED);
+        server
+            .get("/api/brands/1")
+            .add_header("If-None-Match", "\"lama\"")
+            .await
+            .assert_status_ok();
+
+        server
+            .get("/api/brands/1")
+            .add_header("If-Modified-Since", &last_modified)
+            .await
+            .assert_status(StatusCode::NOT_MODIFIED);
+        server
+            .get("/api/brands/1")
+            .add_header("If-Modified-Since", "Sun, 16 Mar 2025 08:00:00 GMT")
+            .await
+            .assert_status_ok();
+
+        server
+            .get("/api/brands/2")
+            .add_header("If-None-Match", "*")
+            .await
+            .assert_status_not_found();
+    }
+
+    #[tokio::test]
+    async fn test_if_match() {
+        let server = server().await;
+        let etag = server
+            .get("/api/brands/1")
+            .await
+            .header("ETag")
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let response = server
+            .put("/api/brands/1")
+            .add_header("If-Match", &etag)
+            .json(&request("Xiaomi"))
+            .await;
+        response.assert_status_ok();
+        let updated = response.header("ETag").to_str().unwrap().to_string();
+        assert_ne!(updated, etag);
+
+        let response = server
+            .put("/api/brands/1")
+            .add_header("If-Match", &etag)
+            .json(&request("Oppo"))
+            .await;
+        response.assert_status(StatusCode::PRECONDITION_FAILED);
+        let problem: Problem = response.json();
+        assert_eq!(problem.extensions["etag"], updated.as_str());
+        let brand: Brand = server.get("/api/brands/1").await.json();
+        assert_eq!(brand.name, "Xiaomi");
+
+        server
+            .put("/api/brands/1")
+            .add_header("If-Unmodified-Since", "Sun, 16 Mar 2025 08:00:00 GMT")
+            .json(&request("Oppo"))
+            .await
+            .assert_status(StatusCode::PRECONDITION_FAILED);
+
+        server
+            .delete("/api/brands/1")
+            .add_header("If-Match", &etag)
+            .await
+            .assert_status(StatusCode::PRECONDITION_FAILED);
+        server
+            .delete("/api/brands/1")
+            .add_header("If-Match", "*")
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn test_if_match_is_checked_by_the_write() {
+        let repository = Arc::new(MemoryRepository::new());
+        let racing = Arc::new(Racing {
+            inner: repository.clone(),
+            armed: AtomicBool::new(false),
+        });
+        let mut state = AppState::new(
+            repository,
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        );
+        state.brands = racing.clone();
+        let server = TestServer::new(router(state)).unwrap();
+        server.post("/api/brands").json(&request("Samsung")).await;
+        let etag = server
+            .get("/api/brands/1")
+            .await
+            .header("ETag")
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        racing.armed.store(true, Ordering::SeqCst);
+        let response = server
+            .put("/api/brands/1")
+            .add_header("If-Match", &etag)
+            .json(&request("Oppo"))
+            .await;
+        response.assert_status(StatusCode::PRECONDITION_FAILED);
+        let brand: Brand = server.get("/api/brands/1").await.json();
+        assert_eq!(brand.name, "Vivo");
+
+        let etag = server
+            .get("/api/brands/1")
+            .await
+            .header("ETag")
+            .to_str()
+            .unwrap()
+            .to_string();
+        racing.armed.store(true, Ordering::SeqCst);
+        server
+            .delete("/api/brands/1")
+            .add_header("If-Match", &etag)
+            .await
+            .assert_status(StatusCode::PRECONDITION_FAILED);
+        server.get("/api/brands/1").await.assert_status_ok();
+    }
+
+    #[tokio::test]
+    async fn test_last_modified_is_utc() {
+        let jakarta = FixedOffset::east_opt(7 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2025, 3, 16, 15, 0, 0)
+            .unwrap();
+        let brand = Brand {
+            id: 1,
+            name: "Samsung".to_string(),
+            description: None,
+            created_at: jakarta.naive_utc(),
+            updated_at: jakarta.naive_utc(),
+        };
+        let last_modified = brand.validator().last_modified.unwrap();
+        assert_eq!(
+            format_http_date(last_modified),
+            "Sun, 16 Mar 2025 08:00:00 GMT"
+        );
+
+        let server = server().await;
+        let response = server.get("/api/brands/1").await;
+        let last_modified =
+            parse_http_date(response.header("Last-Modified").to_str().unwrap()).unwrap();
+        let drift = Utc::now().naive_utc() - last_modified;
+        assert!(drift.num_seconds().abs() < 5);
+    }
+
+    #[tokio::test]
+    async fn test_etag_per_representation() {
+        let server = server().await;
+
+        let json = server.get("/api/brands/1").await;
+        let toml = server
+            .get("/api/brands/1")
+            .add_header("Accept", "application/toml")
+            .await;
+        let json_etag = json.header("ETag").to_str().unwrap().to_string();
+        let toml_etag = toml.header("ETag").to_str().unwrap().to_string();
+        assert_ne!(json_etag, toml_etag);
+
+        server
+            .get("/api/brands/1")
+            .add_header("Accept", "application/toml")
+            .add_header("If-None-Match", &json_etag)
+            .await
+            .assert_status_ok();
+        server
+            .get("/api/brands/1")
+            .add_header("Accept", "application/toml")
+            .add_header("If-None-Match", &toml_etag)
+            .await
+            .assert_status(StatusCode::NOT_MODIFIED);
+
+        server
+            .put("/api/brands/1")
+            .add_header("If-Match", &toml_etag)
+            .json(&request("Xiaomi"))
+            .await
+            .assert_status_ok();
+    }
+}
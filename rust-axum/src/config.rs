@@ -0,0 +1,659 @@
+use std::time::Duration;
+
+use axum_extra::extract::cookie::Key;
+
+use config::{Config, ConfigError, Environment, File, FileFormat};
+use serde::Deserialize;
+use sqlx::{
+    Pool, Postgres,
+    postgres::{PgConnectOptions, PgPoolOptions},
+};
+
+use crate::{
+    access_log::LogFormat,
+    assets::AssetSettings,
+    auth::{ApiKey, ApiKeys, TokenService},
+    health::HealthChecks,
+    idempotency::Idempotency,
+    jobs::{JobRegistry, JobSettings},
+    live::{EventBus, LiveSettings},
+    rate_limit::{RateLimitRule, RateLimiter, RouteLimit},
+    security::SecuritySettings,
+    session::SessionSettings,
+    timeout::{RouteTimeout, Timeouts},
+    upload::{LocalStorage, UploadLimits},
+    view::Templates,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct AppConfig {
+    pub name: String,
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub auth: AuthConfig,
+    pub log: LogConfig,
+    pub upload: UploadConfig,
+    pub session: SessionConfig,
+    pub health: HealthConfig,
+    pub rate_limit: RateLimitConfig,
+    pub security: SecuritySettings,
+    pub live: LiveConfig,
+    pub jobs: JobsConfig,
+    pub templates: TemplatesConfig,
+    pub assets: AssetsConfig,
+    pub idempotency: IdempotencyConfig,
+    pub timeout: TimeoutConfig,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    pub shutdown_timeout_secs: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DatabaseConfig {
+    pub host: String,
+    pub port: u16,
+    pub name: String,
+    pub user: String,
+    pub password: String,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout_secs: u64,
+    pub idle_timeout_secs: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuthConfig {
+    pub secret: String,
+    pub token_ttl_secs: u64,
+    #[serde(default)]
+    pub api_keys: Vec<ApiKey>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogConfig {
+    pub format: LogFormat,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UploadConfig {
+    pub directory: String,
+    pub max_file_size: u64,
+    pub max_total_size: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SessionConfig {
+    pub secret: String,
+    pub cookie_name: String,
+    pub secure: bool,
+    pub idle_timeout_secs: u64,
+    pub absolute_timeout_secs: u64,
+    pub cleanup_interval_secs: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HealthConfig {
+    pub timeout_ms: u64,
+    pub degraded_ms: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RateLimitConfig {
+    pub capacity: u32,
+    pub period_secs: u64,
+    #[serde(default)]
+    pub routes: Vec<RouteLimit>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LiveConfig {
+    pub buffer: usize,
+    pub heartbeat_secs: u64,
+    pub idle_timeout_secs: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JobsConfig {
+    pub replay: usize,
+    pub keep_alive_secs: u64,
+    pub retention_secs: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TemplatesConfig {
+    pub directory: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssetsConfig {
+    pub directory: String,
+    pub max_age_secs: u64,
+    pub immutable_max_age_secs: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdempotencyConfig {
+    pub ttl_secs: u64,
+    pub lock_timeout_secs: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TimeoutConfig {
+    pub default_ms: u64,
+    #[serde(default)]
+    pub routes: Vec<RouteTimeout>,
+}
+
+impl AppConfig {
+    pub fn load(path: &str) -> Result<Self, ConfigError> {
+        Self::load_with_env(path, Environment::with_prefix("APP"))
+    }
+
+    pub fn load_with_env(path: &str, environment: Environment) -> Result<Self, ConfigError> {
+        let config: AppConfig = Config::builder()
+            .add_source(File::new(path, FileFormat::Toml))
+            .add_source(
+                environment
+                    .prefix_separator("_")
+                    .separator("__")
+                    .try_parsing(true),
+            )
+            .build()?
+            .try_deserialize()?;
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.database.min_connections > self.database.max_connections {
+            return Err(ConfigError::Message(format!(
+                "database.min_connections ({}) must not be greater than database.max_connections ({})",
+                self.database.min_connections, self.database.max_connections
+            )));
+        }
+
+        if self.upload.max_file_size > self.upload.max_total_size {
+            return Err(ConfigError::Message(format!(
+                "upload.max_file_size ({}) must not be greater than upload.max_total_size ({})",
+                self.upload.max_file_size, self.upload.max_total_size
+            )));
+        }
+
+        if self.auth.secret.len() < 32 {
+            return Err(ConfigError::Message(
+                "auth.secret must be at least 32 characters".to_string(),
+            ));
+        }
+
+        if self.session.secret.len() < 32 {
+            return Err(ConfigError::Message(
+                "session.secret must be at least 32 characters".to_string(),
+            ));
+        }
+
+        if (self.security.body_limits.multipart as u64) < self.upload.max_total_size {
+            return Err(ConfigError::Message(format!(
+                "security.body_limits.multipart ({}) must not be smaller than upload.max_total_size ({})",
+                self.security.body_limits.multipart, self.upload.max_total_size
+            )));
+        }
+
+        if self.live.idle_timeout_secs <= self.live.heartbeat_secs {
+            return Err(ConfigError::Message(format!(
+                "live.idle_timeout_secs ({}) must be greater than live.heartbeat_secs ({})",
+                self.live.idle_timeout_secs, self.live.heartbeat_secs
+            )));
+        }
+
+        self.session
+            .validate()
+            .map_err(|err| ConfigError::Message(format!("session: {}", err)))?;
+
+        self.live
+            .validate()
+            .map_err(|err| ConfigError::Message(format!("live: {}", err)))?;
+
+        self.jobs
+            .validate()
+            .map_err(|err| ConfigError::Message(format!("jobs: {}", err)))?;
+
+        self.health
+            .validate()
+            .map_err(|err| ConfigError::Message(format!("health: {}", err)))?;
+
+        self.timeout
+            .validate()
+            .map_err(|err| ConfigError::Message(format!("timeout: {}", err)))?;
+
+        if let Some(longest) = self.timeout.timeouts().longest()
+            && Duration::from_secs(self.idempotency.lock_timeout_secs) <= longest
+        {
+            return Err(ConfigError::Message(format!(
+                "idempotency.lock_timeout_secs ({}) must be greater than the longest request timeout ({} ms)",
+                self.idempotency.lock_timeout_secs,
+                longest.as_millis()
+            )));
+        }
+
+        self.rate_limit
+            .validate()
+            .map_err(|err| ConfigError::Message(format!("rate_limit: {}", err)))?;
+
+        self.security
+            .validate()
+            .map_err(|err| ConfigError::Message(format!("security: {}", err)))?;
+
+        Ok(())
+    }
+}
+
+impl ServerConfig {
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout_secs)
+    }
+}
+
+impl DatabaseConfig {
+    pub fn connect_options(&self) -> PgConnectOptions {
+        PgConnectOptions::new()
+            .host(&self.host)
+            .port(self.port)
+            .database(&self.name)
+            .username(&self.user)
+            .password(&self.password)
+    }
+
+    pub fn pool_options(&self) -> PgPoolOptions {
+        PgPoolOptions::new()
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .acquire_timeout(Duration::from_secs(self.acquire_timeout_secs))
+            .idle_timeout(Duration::from_secs(self.idle_timeout_secs))
+    }
+
+    pub async fn connect(&self) -> Result<Pool<Postgres>, sqlx::Error> {
+        self.pool_options()
+            .connect_with(self.connect_options())
+            .await
+    }
+}
+
+impl AuthConfig {
+    pub fn token_service(&self) -> TokenService {
+        TokenService::new(
+            self.secret.as_bytes(),
+            Duration::from_secs(self.token_ttl_secs),
+        )
+    }
+
+    pub fn api_keys(&self) -> ApiKeys {
+        ApiKeys::new(self.api_keys.iter().cloned())
+    }
+}
+
+impl UploadConfig {
+    pub fn storage(&self) -> LocalStorage {
+        LocalStorage::new(&self.directory)
+    }
+
+    pub fn limits(&self) -> UploadLimits {
+        UploadLimits {
+            max_file_size: self.max_file_size,
+            max_total_size: self.max_total_size,
+        }
+    }
+}
+
+impl SessionConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.cleanup_interval_secs == 0 {
+            return Err("cleanup_interval_secs must be greater than 0".to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn key(&self) -> Key {
+        Key::derive_from(self.secret.as_bytes())
+    }
+
+    pub fn settings(&self) -> SessionSettings {
+        SessionSettings {
+            cookie_name: self.cookie_name.clone(),
+            idle_timeout: Duration::from_secs(self.idle_timeout_secs),
+            absolute_timeout: Duration::from_secs(self.absolute_timeout_secs),
+            secure: self.secure,
+        }
+    }
+
+    pub fn cleanup_interval(&self) -> Duration {
+        Duration::from_secs(self.cleanup_interval_secs)
+    }
+}
+
+impl HealthConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.timeout_ms == 0 {
+            return Err("timeout_ms must be greater than 0".to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn checks(&self) -> HealthChecks {
+        HealthChecks::new(Duration::from_millis(self.timeout_ms))
+    }
+
+    pub fn degraded_after(&self) -> Duration {
+        Duration::from_millis(self.degraded_ms)
+    }
+}
+
+impl RateLimitConfig {
+    pub fn rule(&self) -> Result<RateLimitRule, String> {
+        RateLimitRule::new(self.capacity, self.period_secs)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.rule()?;
+        for route in &self.routes {
+            route.rule()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn limiter(&self) -> RateLimiter {
+        let limiter = match self.rule() {
+            Ok(rule) => RateLimiter::default().with_default(rule),
+            Err(err) => {
+                eprintln!("Default rate limit is disabled : {}", err);
+                RateLimiter::default()
+            }
+        };
+        self.routes
+            .iter()
+            .cloned()
+            .fold(limiter, RateLimiter::with_route)
+    }
+}
+
+impl LiveConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.heartbeat_secs == 0 {
+            return Err("heartbeat_secs must be greater than 0".to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn events(&self) -> EventBus {
+        EventBus::new(self.buffer)
+    }
+
+    pub fn settings(&self) -> LiveSettings {
+        LiveSettings {
+            heartbeat_interval: Duration::from_secs(self.heartbeat_secs),
+            idle_timeout: Duration::from_secs(self.idle_timeout_secs),
+        }
+    }
+}
+
+impl JobsConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.keep_alive_secs == 0 {
+            return Err("keep_alive_secs must be greater than 0".to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn registry(&self) -> JobRegistry {
+        JobRegistry::new(JobSettings {
+            replay: self.replay,
+            keep_alive: Duration::from_secs(self.keep_alive_secs),
+            retention: Duration::from_secs(self.retention_secs),
+        })
+    }
+}
+
+impl TemplatesConfig {
+    pub fn load(&self) -> anyhow::Result<Templates> {
+        Templates::load(&self.directory)
+    }
+}
+
+impl AssetsConfig {
+    pub fn settings(&self) -> AssetSettings {
+        AssetSettings {
+            directory: self.directory.clone().into(),
+            max_age: Duration::from_secs(self.max_age_secs),
+            immutable_max_age: Duration::from_secs(self.immutable_max_age_secs),
+        }
+    }
+}
+
+impl IdempotencyConfig {
+    pub fn idempotency(&self) -> Idempotency {
+        Idempotency::default()
+            .with_ttl(Duration::from_secs(self.ttl_secs))
+            .with_lock_timeout(Duration::from_secs(self.lock_timeout_secs))
+    }
+}
+
+impl TimeoutConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.default_ms == 0 {
+            return Err("default_ms must be greater than 0".to_string());
+        }
+        if let Some(route) = self.routes.iter().find(|route| route.timeout_ms == 0) {
+            return Err(format!(
+                "route {}: timeout_ms must be greater than 0",
+                route.prefix
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn timeouts(&self) -> Timeouts {
+        self.routes.iter().cloned().fold(
+            Timeouts::new().with_default(Duration::from_millis(self.default_ms)),
+            Timeouts::with_route,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use config::Environment;
+
+    use super::AppConfig;
+
+    fn load(env: &[(&str, &str)]) -> Result<AppConfig, config::ConfigError> {
+        let source: HashMap<String, String> = env
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+
+        AppConfig::load_with_env(
+            "application.toml",
+            Environment::with_prefix("APP").source(Some(source)),
+        )
+    }
+
+    #[test]
+    fn test_load_config() {
+        let config = load(&[]).unwrap();
+
+        assert_eq!(config.name, "Rust Axum");
+        assert_eq!(config.server.address(), "0.0.0.0:3000");
+        assert_eq!(config.database.host, "localhost");
+        assert_eq!(config.database.port, 5432);
+        assert_eq!(config.database.max_connections, 10);
+        assert_eq!(config.database.min_connections, 5);
+        assert_eq!(config.upload.directory, "uploads");
+        assert_eq!(config.upload.limits().max_file_size, 2097152);
+        assert_eq!(
+            config
+                .rate_limit
+                .limiter()
+                .rule_for("/api/users/login")
+                .map(|(name, rule)| (name.to_string(), rule.capacity())),
+            Some(("login".to_string(), 5))
+        );
+        assert_eq!(
+            config.security.cors.allowed_origins,
+            vec!["http://localhost:5173"]
+        );
+        assert_eq!(config.security.body_limits.form, 65536);
+        assert_eq!(config.live.settings().heartbeat_interval.as_secs(), 30);
+        assert_eq!(config.jobs.registry().settings().replay, 100);
+        assert_eq!(config.templates.directory, "templates");
+        assert_eq!(config.assets.settings().max_age.as_secs(), 300);
+        assert_eq!(config.idempotency.ttl_secs, 86400);
+        assert_eq!(
+            config
+                .timeout
+                .timeouts()
+                .timeout_for("/api/uploads")
+                .map(|timeout| timeout.as_secs()),
+            Some(60)
+        );
+    }
+
+    #[test]
+    fn test_environment_overrides_file() {
+        let config = load(&[
+            ("APP_SERVER__PORT", "8080"),
+            ("APP_DATABASE__PASSWORD", "super-secret"),
+            ("APP_LOG__FORMAT", "logfmt"),
+        ])
+        .unwrap();
+
+        assert_eq!(config.server.port, 8080);
+        assert_eq!(config.database.password, "super-secret");
+        assert_eq!(config.log.format, crate::access_log::LogFormat::Logfmt);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let err = load(&[("APP_SERVER__PORT", "http")]).unwrap_err();
+        assert!(err.to_string().contains("server.port"), "{}", err);
+
+        let err = load(&[("APP_DATABASE__MIN_CONNECTIONS", "20")]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("database.min_connections (20) must not be greater"),
+            "{}",
+            err
+        );
+
+        let err = load(&[("APP_AUTH__SECRET", "rahasia")]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("auth.secret must be at least 32 characters"),
+            "{}",
+            err
+        );
+
+        let err = load(&[("APP_SESSION__SECRET", "short")]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("session.secret must be at least 32 characters"),
+            "{}",
+            err
+        );
+
+        for (name, value) in [
+            ("APP_RATE_LIMIT__CAPACITY", "0"),
+            ("APP_RATE_LIMIT__PERIOD_SECS", "0"),
+        ] {
+            let err = load(&[(name, value)]).unwrap_err();
+            assert!(
+                err.to_string().contains("rate_limit:")
+                    && err.to_string().contains("must be greater than 0"),
+                "{}",
+                err
+            );
+        }
+
+        let err = load(&[("APP_SESSION__CLEANUP_INTERVAL_SECS", "0")]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("session: cleanup_interval_secs must be greater than 0"),
+            "{}",
+            err
+        );
+
+        let err = load(&[("APP_LIVE__HEARTBEAT_SECS", "0")]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("live: heartbeat_secs must be greater than 0"),
+            "{}",
+            err
+        );
+
+        let err = load(&[("APP_JOBS__KEEP_ALIVE_SECS", "0")]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("jobs: keep_alive_secs must be greater than 0"),
+            "{}",
+            err
+        );
+
+        let err = load(&[("APP_HEALTH__TIMEOUT_MS", "0")]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("health: timeout_ms must be greater than 0"),
+            "{}",
+            err
+        );
+
+        let err = load(&[("APP_TIMEOUT__DEFAULT_MS", "0")]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("timeout: default_ms must be greater than 0"),
+            "{}",
+            err
+        );
+
+        let err = load(&[("APP_IDEMPOTENCY__LOCK_TIMEOUT_SECS", "60")]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("idempotency.lock_timeout_secs (60) must be greater than the longest request timeout (60000 ms)"),
+            "{}",
+            err
+        );
+
+        let err = load(&[("APP_SECURITY__BODY_LIMITS__MULTIPART", "1024")]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("security.body_limits.multipart (1024) must not be smaller"),
+            "{}",
+            err
+        );
+
+        let err = load(&[("APP_SECURITY__HEADERS__FRAME_OPTIONS", "DENY\r\n")]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("security: invalid value for header x-frame-options"),
+            "{}",
+            err
+        );
+
+        let err = AppConfig::load("missing.toml").unwrap_err();
+        assert!(err.to_string().contains("missing.toml"), "{}", err);
+    }
+}
@@ -0,0 +1,287 @@
+use axum::{
+    Json,
+    extract::rejection::JsonRejection,
+    response::{IntoResponse, Response},
+};
+use http::{HeaderValue, StatusCode, header::CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationErrors, ValidationErrorsKind};
+
+use crate::request_id::RequestId;
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+#[derive(Debug)]
+pub struct AppError {
+    pub status: StatusCode,
+    pub detail: String,
+    pub extensions: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(flatten)]
+    #[schema(ignore)]
+    pub extensions: Map<String, Value>,
+}
+
+impl AppError {
+    pub fn new(status: StatusCode, detail: impl Into<String>) -> Self {
+        Self {
+            status,
+            detail: detail.into(),
+            extensions: Map::new(),
+        }
+    }
+
+    pub fn with_extension(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.extensions.insert(key.to_string(), value.into());
+        self
+    }
+
+    pub fn unauthorized(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, detail)
+    }
+
+    pub fn not_found(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, detail)
+    }
+
+    pub fn internal(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, detail)
+    }
+
+    pub fn to_problem(&self) -> Problem {
+        Problem {
+            problem_type: "about:blank".to_string(),
+            title: self
+                .status
+                .canonical_reason()
+                .unwrap_or("Unknown Error")
+                .to_string(),
+            status: self.status.as_u16(),
+            detail: self.detail.clone(),
+            request_id: RequestId::current().map(|id| id.to_string()),
+            extensions: self.extensions.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.status, self.detail)
+    }
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::new(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        match err {
+            sqlx::Error::RowNotFound => Self::not_found("Data is not found"),
+            err => {
+                eprintln!("Database error : {}", err);
+                Self::internal("A database error occurred")
+            }
+        }
+    }
+}
+
+fn flatten_errors(prefix: &str, errors: &ValidationErrors, fields: &mut Map<String, Value>) {
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", prefix, field)
+        };
+
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                let errors = errors
+                    .iter()
+                    .map(|error| {
+                        serde_json::json!({
+                            "code": error.code,
+                            "message": error.message,
+                        })
+                    })
+                    .collect();
+                fields.insert(path, Value::Array(errors));
+            }
+            ValidationErrorsKind::Struct(errors) => flatten_errors(&path, errors, fields),
+            ValidationErrorsKind::List(items) => {
+                for (index, errors) in items {
+                    flatten_errors(&format!("{}[{}]", path, index), errors, fields);
+                }
+            }
+        }
+    }
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(errors: ValidationErrors) -> Self {
+        let mut fields = Map::new();
+        flatten_errors("", &errors, &mut fields);
+
+        Self::new(StatusCode::UNPROCESSABLE_ENTITY, "Validation failed")
+            .with_extension("errors", fields)
+    }
+}
+
+/// Validates every item of a list body, keying field errors by the item
+/// index (`"3.name"`) so clients can find the element that failed.
+pub fn validate_list<T: Validate>(items: &[T]) -> Result<(), AppError> {
+    let mut fields = Map::new();
+    for (index, item) in items.iter().enumerate() {
+        if let Err(errors) = item.validate() {
+            flatten_errors(&index.to_string(), &errors, &mut fields);
+        }
+    }
+
+    if fields.is_empty() {
+        Ok(())
+    } else {
+        Err(
+            AppError::new(StatusCode::UNPROCESSABLE_ENTITY, "Validation failed")
+                .with_extension("errors", fields),
+        )
+    }
+}
+
+impl From<anyhow::Error> for AppError {
+    fn from(err: anyhow::Error) -> Self {
+        eprintln!("Unexpected error : {:#}", err);
+        Self::internal("An unexpected error occurred")
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let mut response = (self.status, Json(self.to_problem())).into_response();
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+    use axum::{
+        Json, Router,
+        middleware::from_fn,
+        routing::{get, post},
+    };
+    use axum_test::TestServer;
+    use http::StatusCode;
+    use serde::{Deserialize, Serialize};
+    use validator::Validate;
+
+    use super::{AppError, PROBLEM_JSON, Problem};
+    use crate::request_id::request_id_middleware;
+
+    #[derive(Serialize, Deserialize, Validate)]
+    struct Payload {
+        #[validate(length(min = 3))]
+        name: String,
+    }
+
+    fn server() -> TestServer {
+        async fn teapot() -> Result<String, AppError> {
+            Err(AppError::new(StatusCode::IM_A_TEAPOT, "No coffee"))
+        }
+
+        async fn database() -> Result<String, AppError> {
+            Err(sqlx::Error::PoolTimedOut)?
+        }
+
+        async fn unexpected() -> Result<String, AppError> {
+            Err(anyhow!("secret connection string"))?
+        }
+
+        async fn validate(payload: Json<Payload>) -> Result<String, AppError> {
+            payload.validate()?;
+            Ok(payload.name.clone())
+        }
+
+        async fn json(
+            payload: Result<Json<Payload>, axum::extract::rejection::JsonRejection>,
+        ) -> Result<String, AppError> {
+            Ok(payload?.name.clone())
+        }
+
+        let app = Router::new()
+            .route("/teapot", get(teapot))
+            .route("/database", get(database))
+            .route("/unexpected", get(unexpected))
+            .route("/validate", post(validate))
+            .route("/json", post(json))
+            .layer(from_fn(request_id_middleware));
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_problem_json() {
+        let server = server();
+
+        let response = server
+            .get("/teapot")
+            .add_header("X-Request-ID", "request-1")
+            .await;
+        response.assert_status(StatusCode::IM_A_TEAPOT);
+        response.assert_header("Content-Type", PROBLEM_JSON);
+
+        let problem: Problem = response.json();
+        assert_eq!(problem.problem_type, "about:blank");
+        assert_eq!(problem.title, "I'm a teapot");
+        assert_eq!(problem.status, 418);
+        assert_eq!(problem.detail, "No coffee");
+        assert_eq!(problem.request_id.as_deref(), Some("request-1"));
+    }
+
+    #[tokio::test]
+    async fn test_problem_conversions() {
+        let server = server();
+
+        let response = server.get("/database").await;
+        response.assert_status_internal_server_error();
+        let problem: Problem = response.json();
+        assert_eq!(problem.detail, "A database error occurred");
+
+        let response = server.get("/unexpected").await;
+        response.assert_status_internal_server_error();
+        let problem: Problem = response.json();
+        assert_eq!(problem.detail, "An unexpected error occurred");
+
+        let response = server
+            .post("/validate")
+            .json(&Payload {
+                name: "ab".to_string(),
+            })
+            .await;
+        response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        let problem: Problem = response.json();
+        assert_eq!(problem.extensions["errors"]["name"][0]["code"], "length");
+
+        let response = server.post("/json").text("tidak valid").await;
+        response.assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        let problem: Problem = response.json();
+        assert_eq!(problem.status, 415);
+        assert!(problem.request_id.is_some());
+    }
+}
@@ -0,0 +1,440 @@
+use axum::{
+    Form,
+    body::Bytes,
+    extract::{FromRequest, Request},
+    response::{IntoResponse, Response},
+};
+use http::{HeaderMap, StatusCode, header::CONTENT_TYPE};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::error::Category;
+use validator::ValidateArgs;
+
+use crate::error::AppError;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json<T>(pub T);
+
+fn is_json_content_type(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+
+    essence == "application/json"
+        || (essence.starts_with("application/") && essence.ends_with("+json"))
+}
+
+impl<T> Json<T>
+where
+    T: DeserializeOwned,
+{
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, AppError> {
+        let deserializer = &mut serde_json::Deserializer::from_slice(bytes);
+
+        serde_path_to_error::deserialize(deserializer)
+            .map(Json)
+            .map_err(|err| {
+                let path = err.path().to_string();
+                let inner = err.into_inner();
+
+                match inner.classify() {
+                    Category::Data => AppError::new(
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        format!("Invalid value at `{}`: {}", path, inner),
+                    )
+                    .with_extension("path", path),
+                    Category::Syntax | Category::Eof | Category::Io => AppError::new(
+                        StatusCode::BAD_REQUEST,
+                        format!("Malformed JSON body: {}", inner),
+                    )
+                    .with_extension("line", inner.line())
+                    .with_extension("column", inner.column()),
+                }
+            })
+    }
+}
+
+impl<T, S> FromRequest<S> for Json<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !is_json_content_type(request.headers()) {
+            return Err(AppError::new(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "Expected request with `Content-Type: application/json`",
+            ));
+        }
+
+        let bytes = Bytes::from_request(request, state)
+            .await
+            .map_err(|rejection| AppError::new(rejection.status(), rejection.body_text()))?;
+
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl<T> IntoResponse for Json<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        axum::Json(self.0).into_response()
+    }
+}
+
+pub trait ValidationArgs<'v, S> {
+    fn from_state(state: &'v S) -> Self;
+}
+
+impl<'v, S> ValidationArgs<'v, S> for () {
+    fn from_state(_state: &'v S) -> Self {}
+}
+
+impl<'v, S, C> ValidationArgs<'v, S> for &'v C
+where
+    S: AsRef<C>,
+{
+    fn from_state(state: &'v S) -> Self {
+        state.as_ref()
+    }
+}
+
+pub fn validate<T, S>(value: &T, state: &S) -> Result<(), AppError>
+where
+    T: for<'v> ValidateArgs<'v>,
+    for<'v> <T as ValidateArgs<'v>>::Args: ValidationArgs<'v, S>,
+{
+    let args = <T as ValidateArgs<'_>>::Args::from_state(state);
+    value.validate_with_args(args)?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidatedJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned + for<'v> ValidateArgs<'v>,
+    for<'v> <T as ValidateArgs<'v>>::Args: ValidationArgs<'v, S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(request, state).await?;
+        validate(&value, state)?;
+
+        Ok(ValidatedJson(value))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidatedForm<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidatedForm<T>
+where
+    T: DeserializeOwned + for<'v> ValidateArgs<'v>,
+    for<'v> <T as ValidateArgs<'v>>::Args: ValidationArgs<'v, S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Form(value) = Form::<T>::from_request(request, state)
+            .await
+            .map_err(|rejection| AppError::new(rejection.status(), rejection.body_text()))?;
+        validate(&value, state)?;
+
+        Ok(ValidatedForm(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{Router, body::Bytes, extract::DefaultBodyLimit, routing::post};
+    use axum_test::TestServer;
+    use http::StatusCode;
+    use serde::{Deserialize, Serialize};
+
+    use super::{Json, ValidatedForm, ValidatedJson};
+    use crate::{
+        auth::LoginRequest,
+        error::Problem,
+        validation::{
+            AddressRequest, DatabaseContext, Product, ProductVariant, RegisterUserRequest,
+        },
+    };
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Address {
+        city: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Person {
+        name: String,
+        address: Address,
+    }
+
+    fn server() -> TestServer {
+        async fn route(Json(person): Json<Person>) -> Json<String> {
+            Json(format!(
+                "Hello {} from {}",
+                person.name, person.address.city
+            ))
+        }
+
+        let app = Router::new()
+            .route("/post", post(route))
+            .layer(DefaultBodyLimit::max(64));
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_json_success() {
+        let server = server();
+
+        let response = server
+            .post("/post")
+            .json(&Person {
+                name: "Rizki".to_string(),
+                address: Address {
+                    city: "Jakarta".to_string(),
+                },
+            })
+            .await;
+        response.assert_status_ok();
+        response.assert_json(&"Hello Rizki from Jakarta".to_string());
+    }
+
+    #[tokio::test]
+    async fn test_json_missing_content_type() {
+        let server = server();
+
+        let response = server.post("/post").text("tidak valid").await;
+        response.assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        let problem: Problem = response.json();
+        assert_eq!(
+            problem.detail,
+            "Expected request with `Content-Type: application/json`"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_json_syntax_error() {
+        let server = server();
+
+        let response = server
+            .post("/post")
+            .bytes(Bytes::from("{\n  \"name\": \"Rizki\",\n  oops\n}"))
+            .content_type("application/json")
+            .await;
+        response.assert_status_bad_request();
+        let problem: Problem = response.json();
+        assert_eq!(problem.extensions["line"], 3);
+        assert_eq!(problem.extensions["column"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_json_data_error() {
+        let server = server();
+
+        let response = server
+            .post("/post")
+            .bytes(Bytes::from(r#"{"name": "Rizki", "address": {"city": 10}}"#))
+            .content_type("application/merge-patch+json")
+            .await;
+        response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        let problem: Problem = response.json();
+        assert_eq!(problem.extensions["path"], "address.city");
+        assert!(
+            problem
+                .detail
+                .starts_with("Invalid value at `address.city`")
+        );
+
+        let response = server
+            .post("/post")
+            .bytes(Bytes::from(r#"{"address": {"city": "Jakarta"}}"#))
+            .content_type("application/json")
+            .await;
+        response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        let problem: Problem = response.json();
+        assert!(problem.detail.contains("missing field `name`"));
+    }
+
+    #[tokio::test]
+    async fn test_json_body_too_large() {
+        let server = server();
+
+        let response = server
+            .post("/post")
+            .bytes(Bytes::from(format!(r#"{{"name": "{}"}}"#, "a".repeat(100))))
+            .content_type("application/json")
+            .await;
+        response.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+        let problem: Problem = response.json();
+        assert_eq!(problem.status, 413);
+    }
+
+    #[derive(Clone)]
+    struct ValidationState {
+        database: Arc<DatabaseContext>,
+    }
+
+    impl AsRef<DatabaseContext> for ValidationState {
+        fn as_ref(&self) -> &DatabaseContext {
+            &self.database
+        }
+    }
+
+    fn validation_server(total: i32) -> TestServer {
+        async fn register(ValidatedJson(request): ValidatedJson<RegisterUserRequest>) -> String {
+            format!("Hello {}", request.username)
+        }
+
+        async fn product(ValidatedJson(product): ValidatedJson<Product>) -> String {
+            format!("Product {}", product.id)
+        }
+
+        async fn login(ValidatedForm(request): ValidatedForm<LoginRequest>) -> String {
+            format!("Hello {}", request.username)
+        }
+
+        let state = ValidationState {
+            database: Arc::new(DatabaseContext {
+                total,
+                max_data: 100,
+            }),
+        };
+
+        let app = Router::new()
+            .route("/register", post(register))
+            .route("/products", post(product))
+            .route("/login", post(login))
+            .with_state(state);
+        TestServer::new(app).unwrap()
+    }
+
+    fn register_request(password: &str, city: &str) -> RegisterUserRequest {
+        RegisterUserRequest {
+            username: "rizki".to_string(),
+            password: "password".to_string(),
+            confirm_password: password.to_string(),
+            name: "Rizki Harahap".to_string(),
+            address: AddressRequest {
+                street: "Jl. Jalan".to_string(),
+                city: city.to_string(),
+                country: "Indonesia".to_string(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validated_json_with_context() {
+        let server = validation_server(10);
+
+        let response = server
+            .post("/register")
+            .json(&register_request("password", "Jakarta"))
+            .await;
+        response.assert_status_ok();
+        response.assert_text("Hello rizki");
+
+        let response = server
+            .post("/register")
+            .json(&register_request("salah", ""))
+            .await;
+        response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        let problem: Problem = response.json();
+        let errors = &problem.extensions["errors"];
+        assert_eq!(errors["address.city"][0]["code"], "length");
+        assert_eq!(errors["__all__"][0]["code"], "password");
+
+        let server = validation_server(100);
+        let response = server
+            .post("/register")
+            .json(&register_request("password", "Jakarta"))
+            .await;
+        response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        let problem: Problem = response.json();
+        assert_eq!(
+            problem.extensions["errors"]["__all__"][0]["message"],
+            "cannot register user Rizki Harahap, database is full"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validated_json_nested_list() {
+        let server = validation_server(10);
+
+        let response = server
+            .post("/products")
+            .json(&Product {
+                id: "product-1".to_string(),
+                name: "Product 1".to_string(),
+                variants: vec![
+                    ProductVariant {
+                        name: "Variant 1".to_string(),
+                        price: 1000,
+                    },
+                    ProductVariant {
+                        name: "".to_string(),
+                        price: -1000,
+                    },
+                ],
+            })
+            .await;
+        response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        let problem: Problem = response.json();
+        let errors = problem.extensions["errors"].as_object().unwrap();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors["variants[1].name"][0]["code"], "length");
+        assert_eq!(errors["variants[1].price"][0]["code"], "range");
+    }
+
+    #[tokio::test]
+    async fn test_validated_form() {
+        let server = validation_server(10);
+
+        let response = server
+            .post("/login")
+            .form(&LoginRequest {
+                username: "rizki".to_string(),
+                password: "rahasia".to_string(),
+            })
+            .await;
+        response.assert_status_ok();
+        response.assert_text("Hello rizki");
+
+        let response = server
+            .post("/login")
+            .form(&LoginRequest {
+                username: "ri".to_string(),
+                password: "rahasia".to_string(),
+            })
+            .await;
+        response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        let problem: Problem = response.json();
+        assert_eq!(
+            problem.extensions["errors"]["username"][0]["message"],
+            "username must be between 3 and 20 characters"
+        );
+    }
+}
@@ -0,0 +1,375 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use axum::{extract::State, response::IntoResponse};
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+use tokio::{task::JoinSet, time::timeout};
+use utoipa::ToSchema;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{app::AppState, extract::Json, view::Templates};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Up,
+    Degraded,
+    Down,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckOutcome {
+    Up,
+    Degraded(String),
+    Down(String),
+}
+
+#[async_trait]
+pub trait HealthCheck: Send + Sync {
+    fn name(&self) -> &str;
+    async fn check(&self) -> CheckOutcome;
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CheckReport {
+    pub name: String,
+    pub status: HealthStatus,
+    pub latency_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub checks: Vec<CheckReport>,
+}
+
+#[derive(Clone)]
+pub struct HealthChecks {
+    checks: Vec<Arc<dyn HealthCheck>>,
+    timeout: Duration,
+}
+
+impl Default for HealthChecks {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(2))
+    }
+}
+
+impl HealthChecks {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            checks: Vec::new(),
+            timeout,
+        }
+    }
+
+    pub fn register(&mut self, check: Arc<dyn HealthCheck>) {
+        self.checks.push(check);
+    }
+
+    pub async fn run(&self) -> HealthReport {
+        let mut tasks = JoinSet::new();
+
+        for (index, check) in self.checks.iter().enumerate() {
+            let check = check.clone();
+            let limit = self.timeout;
+
+            tasks.spawn(async move {
+                let start = Instant::now();
+                let outcome = timeout(limit, check.check())
+                    .await
+                    .unwrap_or_else(|_| CheckOutcome::Down(format!("timed out after {:?}", limit)));
+                let (status, message) = match outcome {
+                    CheckOutcome::Up => (HealthStatus::Up, None),
+                    CheckOutcome::Degraded(message) => (HealthStatus::Degraded, Some(message)),
+                    CheckOutcome::Down(message) => (HealthStatus::Down, Some(message)),
+                };
+
+                (
+                    index,
+                    CheckReport {
+                        name: check.name().to_string(),
+                        status,
+                        latency_ms: start.elapsed().as_secs_f64() * 1000.0,
+                        message,
+                    },
+                )
+            });
+        }
+
+        let mut checks: Vec<(usize, CheckReport)> = Vec::with_capacity(self.checks.len());
+        while let Some(result) = tasks.join_next().await {
+            match result {
+                Ok(report) => checks.push(report),
+                Err(err) => eprintln!("Health check task failed : {}", err),
+            }
+        }
+        checks.sort_by_key(|(index, _)| *index);
+
+        let checks: Vec<CheckReport> = checks.into_iter().map(|(_, report)| report).collect();
+        let status = if checks.len() < self.checks.len() {
+            HealthStatus::Down
+        } else {
+            checks
+                .iter()
+                .map(|check| check.status)
+                .max()
+                .unwrap_or(HealthStatus::Up)
+        };
+
+        HealthReport { status, checks }
+    }
+}
+
+pub struct DatabaseCheck {
+    pool: Pool<Postgres>,
+    degraded_after: Duration,
+}
+
+impl DatabaseCheck {
+    pub fn new(pool: Pool<Postgres>, degraded_after: Duration) -> Self {
+        Self {
+            pool,
+            degraded_after,
+        }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for DatabaseCheck {
+    fn name(&self) -> &str {
+        "database"
+    }
+
+    async fn check(&self) -> CheckOutcome {
+        let start = Instant::now();
+
+        match sqlx::query("SELECT 1").execute(&self.pool).await {
+            Ok(_) if start.elapsed() > self.degraded_after => CheckOutcome::Degraded(format!(
+                "SELECT 1 took {:?}, pool size {} with {} idle",
+                start.elapsed(),
+                self.pool.size(),
+                self.pool.num_idle()
+            )),
+            Ok(_) => CheckOutcome::Up,
+            Err(err) => CheckOutcome::Down(err.to_string()),
+        }
+    }
+}
+
+pub struct TemplateCheck {
+    templates: Arc<Templates>,
+    required: Vec<String>,
+}
+
+impl TemplateCheck {
+    pub fn new(templates: Arc<Templates>, required: &[&str]) -> Self {
+        Self {
+            templates,
+            required: required.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for TemplateCheck {
+    fn name(&self) -> &str {
+        "templates"
+    }
+
+    async fn check(&self) -> CheckOutcome {
+        let required: Vec<&str> = self.required.iter().map(String::as_str).collect();
+        let missing = self.templates.missing(&required);
+
+        if missing.is_empty() {
+            CheckOutcome::Up
+        } else {
+            CheckOutcome::Down(format!("missing templates {}", missing.join(", ")))
+        }
+    }
+}
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(live))
+        .routes(routes!(ready))
+}
+
+#[utoipa::path(
+    get,
+    path = "/live",
+    tag = "health",
+    responses((status = 200, body = HealthReport))
+)]
+async fn live() -> Json<HealthReport> {
+    Json(HealthReport {
+        status: HealthStatus::Up,
+        checks: Vec::new(),
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "All checks are up or degraded", body = HealthReport),
+        (status = 503, description = "At least one check is down", body = HealthReport)
+    )
+)]
+async fn ready(State(state): State<AppState>) -> impl IntoResponse {
+    let report = state.health.run().await;
+    let status = match report.status {
+        HealthStatus::Down => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::OK,
+    };
+
+    (status, Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use async_trait::async_trait;
+    use axum_test::TestServer;
+    use http::StatusCode;
+    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+    use tokio::time::sleep;
+
+    use super::{
+        CheckOutcome, DatabaseCheck, HealthCheck, HealthChecks, HealthReport, HealthStatus,
+        TemplateCheck,
+    };
+    use crate::{
+        app::{AppState, router},
+        auth::TokenService,
+        repository::MemoryRepository,
+        view::{REQUIRED, Templates},
+    };
+
+    struct StaticCheck {
+        name: &'static str,
+        outcome: CheckOutcome,
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl HealthCheck for StaticCheck {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn check(&self) -> CheckOutcome {
+            sleep(self.delay).await;
+            self.outcome.clone()
+        }
+    }
+
+    fn check(name: &'static str, outcome: CheckOutcome) -> Arc<dyn HealthCheck> {
+        Arc::new(StaticCheck {
+            name,
+            outcome,
+            delay: Duration::ZERO,
+        })
+    }
+
+    fn server(checks: Vec<Arc<dyn HealthCheck>>) -> TestServer {
+        let mut health = HealthChecks::new(Duration::from_millis(200));
+        for check in checks {
+            health.register(check);
+        }
+
+        let state = AppState::new(
+            Arc::new(MemoryRepository::new()),
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        )
+        .with_health_checks(health);
+        TestServer::new(router(state)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_live_and_ready() {
+        let server = server(vec![
+            check("database", CheckOutcome::Up),
+            check("templates", CheckOutcome::Up),
+        ]);
+
+        let response = server.get("/health/live").await;
+        response.assert_status_ok();
+        let report: HealthReport = response.json();
+        assert_eq!(report.status, HealthStatus::Up);
+
+        let response = server.get("/health/ready").await;
+        response.assert_status_ok();
+        let report: HealthReport = response.json();
+        assert_eq!(report.status, HealthStatus::Up);
+        let names: Vec<&str> = report.checks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["database", "templates"]);
+        assert!(report.checks.iter().all(|c| c.latency_ms >= 0.0));
+    }
+
+    #[tokio::test]
+    async fn test_degraded_and_down() {
+        let degraded = server(vec![
+            check("database", CheckOutcome::Up),
+            check("cache", CheckOutcome::Degraded("cache is cold".to_string())),
+        ]);
+
+        let response = degraded.get("/health/ready").await;
+        response.assert_status_ok();
+        let report: HealthReport = response.json();
+        assert_eq!(report.status, HealthStatus::Degraded);
+        assert_eq!(report.checks[1].message.as_deref(), Some("cache is cold"));
+
+        let down = server(vec![
+            check("cache", CheckOutcome::Degraded("cache is cold".to_string())),
+            Arc::new(StaticCheck {
+                name: "slow",
+                outcome: CheckOutcome::Up,
+                delay: Duration::from_secs(5),
+            }),
+        ]);
+
+        let response = down.get("/health/ready").await;
+        response.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+        let report: HealthReport = response.json();
+        assert_eq!(report.status, HealthStatus::Down);
+        assert_eq!(report.checks[1].status, HealthStatus::Down);
+        assert_eq!(
+            report.checks[1].message.as_deref(),
+            Some("timed out after 200ms")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_database_check() {
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(100))
+            .connect_lazy_with(PgConnectOptions::new().host("127.0.0.1").port(1));
+        let check = DatabaseCheck::new(pool, Duration::from_millis(500));
+
+        assert_eq!(check.name(), "database");
+        assert!(matches!(check.check().await, CheckOutcome::Down(_)));
+    }
+
+    #[tokio::test]
+    async fn test_template_check() {
+        let check = TemplateCheck::new(Arc::new(Templates::load("templates").unwrap()), &REQUIRED);
+        assert_eq!(check.name(), "templates");
+        assert_eq!(check.check().await, CheckOutcome::Up);
+
+        let check = TemplateCheck::new(Arc::new(Templates::default()), &["error", "brands"]);
+        assert_eq!(
+            check.check().await,
+            CheckOutcome::Down("missing templates error, brands".to_string())
+        );
+    }
+}
@@ -0,0 +1,703 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use axum::{
+    body::{Body, Bytes, to_bytes},
+    extract::{ConnectInfo, FromRequestParts, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http::{HeaderName, HeaderValue, Method, StatusCode, header::SET_COOKIE, request::Parts};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    app::AppState, auth::bearer_token, error::AppError, rate_limit::X_API_KEY, session::SessionUser,
+};
+
+pub const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+pub const IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+const MAX_KEY_LENGTH: usize = 255;
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+pub struct StoredResponse {
+    pub status: StatusCode,
+    pub headers: Vec<(HeaderName, HeaderValue)>,
+    pub body: Bytes,
+}
+
+impl IntoResponse for StoredResponse {
+    fn into_response(self) -> Response {
+        let mut response = Response::new(Body::from(self.body));
+        *response.status_mut() = self.status;
+        let headers = response.headers_mut();
+        for (name, value) in self.headers {
+            headers.append(name, value);
+        }
+        headers.insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+
+        response
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub fingerprint: String,
+    pub response: Option<StoredResponse>,
+}
+
+#[async_trait]
+pub trait IdempotencyStore: Send + Sync {
+    async fn begin(
+        &self,
+        key: &str,
+        fingerprint: &str,
+        lock_timeout: Duration,
+    ) -> anyhow::Result<Option<Record>>;
+    async fn complete(
+        &self,
+        key: &str,
+        response: StoredResponse,
+        ttl: Duration,
+    ) -> anyhow::Result<()>;
+    async fn abandon(&self, key: &str) -> anyhow::Result<()>;
+}
+
+struct Entry {
+    record: Record,
+    expires_at: Instant,
+}
+
+struct Entries {
+    entries: HashMap<String, Entry>,
+    swept: Instant,
+}
+
+/// Expired entries are ignored on lookup and evicted by a sweep that runs at
+/// most once per sweep interval.
+pub struct MemoryIdempotencyStore {
+    entries: Mutex<Entries>,
+    sweep_interval: Duration,
+}
+
+impl Default for MemoryIdempotencyStore {
+    fn default() -> Self {
+        Self {
+            entries: Mutex::new(Entries {
+                entries: HashMap::new(),
+                swept: Instant::now(),
+            }),
+            sweep_interval: SWEEP_INTERVAL,
+        }
+    }
+}
+
+impl MemoryIdempotencyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_sweep_interval(mut self, sweep_interval: Duration) -> Self {
+        self.sweep_interval = sweep_interval;
+        self
+    }
+
+    pub fn entries(&self) -> usize {
+        self.entries.lock().unwrap().entries.len()
+    }
+}
+
+#[async_trait]
+impl IdempotencyStore for MemoryIdempotencyStore {
+    async fn begin(
+        &self,
+        key: &str,
+        fingerprint: &str,
+        lock_timeout: Duration,
+    ) -> anyhow::Result<Option<Record>> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+
+        if now.duration_since(entries.swept) >= self.sweep_interval {
+            entries.entries.retain(|_, entry| entry.expires_at > now);
+            entries.swept = now;
+        }
+
+        if let Some(entry) = entries.entries.get(key)
+            && entry.expires_at > now
+        {
+            return Ok(Some(entry.record.clone()));
+        }
+
+        entries.entries.insert(
+            key.to_string(),
+            Entry {
+                record: Record {
+                    fingerprint: fingerprint.to_string(),
+                    response: None,
+                },
+                expires_at: now + lock_timeout,
+            },
+        );
+        Ok(None)
+    }
+
+    async fn complete(
+        &self,
+        key: &str,
+        response: StoredResponse,
+        ttl: Duration,
+    ) -> anyhow::Result<()> {
+        if let Some(entry) = self.entries.lock().unwrap().entries.get_mut(key) {
+            entry.record.response = Some(response);
+            entry.expires_at = Instant::now() + ttl;
+        }
+        Ok(())
+    }
+
+    async fn abandon(&self, key: &str) -> anyhow::Result<()> {
+        self.entries.lock().unwrap().entries.remove(key);
+        Ok(())
+    }
+}
+
+pub struct Idempotency {
+    store: Arc<dyn IdempotencyStore>,
+    ttl: Duration,
+    lock_timeout: Duration,
+}
+
+impl Default for Idempotency {
+    fn default() -> Self {
+        Self::new(Arc::new(MemoryIdempotencyStore::new()))
+    }
+}
+
+impl Idempotency {
+    pub fn new(store: Arc<dyn IdempotencyStore>) -> Self {
+        Self {
+            store,
+            ttl: Duration::from_secs(86400),
+            lock_timeout: Duration::from_secs(60),
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn with_lock_timeout(mut self, lock_timeout: Duration) -> Self {
+        self.lock_timeout = lock_timeout;
+        self
+    }
+}
+
+fn fingerprint(request: &http::request::Parts, body: &[u8]) -> String {
+    let mut digest = Sha256::new();
+    digest.update(request.method.as_str());
+    digest.update([0]);
+    digest.update(request.uri.to_string());
+    digest.update([0]);
+    digest.update(body);
+
+    format!("{:x}", digest.finalize())
+}
+
+/// Anonymous callers are scoped by the peer address, so it must be the real
+/// client address; behind a proxy every caller would share one scope. When the
+/// address is unknown there is no safe scope and the key is ignored.
+async fn principal(state: &AppState, parts: &mut Parts) -> Option<String> {
+    if let Some(claims) =
+        bearer_token(&parts.headers).and_then(|token| state.tokens.verify(token).ok())
+    {
+        return Some(format!("user:{}", claims.sub));
+    }
+    if let Ok(user) = SessionUser::from_request_parts(parts, state).await {
+        return Some(format!("user:{}", user.username));
+    }
+
+    let key = parts
+        .headers
+        .get(X_API_KEY)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|key| state.api_keys.verify(key));
+    if let Some(name) = key {
+        return Some(format!("key:{}", name));
+    }
+
+    parts
+        .extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(client)| format!("ip:{}", client.ip()))
+}
+
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= MAX_KEY_LENGTH
+        && key.bytes().all(|byte| byte.is_ascii_graphic())
+}
+
+/// Abandons the in-flight record when the handler future is dropped before the
+/// response is stored, e.g. because the client disconnected.
+struct InFlight {
+    store: Arc<dyn IdempotencyStore>,
+    key: Option<String>,
+}
+
+impl InFlight {
+    fn new(store: Arc<dyn IdempotencyStore>, key: &str) -> Self {
+        Self {
+            store,
+            key: Some(key.to_string()),
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.key.take().unwrap_or_default()
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        let Some(key) = self.key.take() else {
+            return;
+        };
+        let store = self.store.clone();
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(async move {
+                if let Err(err) = store.abandon(&key).await {
+                    eprintln!("Idempotency store error : {:#}", err);
+                }
+            });
+        }
+    }
+}
+
+async fn store_response(
+    state: &AppState,
+    key: &str,
+    response: Response,
+) -> Result<Response, AppError> {
+    if response.status().is_server_error() {
+        if let Err(err) = state.idempotency.store.abandon(key).await {
+            eprintln!("Idempotency store error : {:#}", err);
+        }
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = to_bytes(body, usize::MAX)
+        .await
+        .map_err(|err| AppError::internal(format!("Failed to buffer response : {}", err)))?;
+    let stored = StoredResponse {
+        status: parts.status,
+        headers: parts
+            .headers
+            .iter()
+            .filter(|(name, _)| *name != SET_COOKIE)
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect(),
+        body: body.clone(),
+    };
+    if let Err(err) = state
+        .idempotency
+        .store
+        .complete(key, stored, state.idempotency.ttl)
+        .await
+    {
+        eprintln!("Idempotency store error : {:#}", err);
+    }
+
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+pub async fn idempotency_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if request.method() != Method::POST {
+        return Ok(next.run(request).await);
+    }
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY) else {
+        return Ok(next.run(request).await);
+    };
+    let key = key
+        .to_str()
+        .ok()
+        .filter(|key| is_valid_key(key))
+        .ok_or_else(|| {
+            AppError::new(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Idempotency-Key must be 1 to {} visible ASCII characters",
+                    MAX_KEY_LENGTH
+                ),
+            )
+        })?
+        .to_string();
+
+    let (mut parts, body) = request.into_parts();
+    let Some(principal) = principal(&state, &mut parts).await else {
+        return Ok(next.run(Request::from_parts(parts, body)).await);
+    };
+    let scope = format!("{}:{}", principal, key);
+    let body = to_bytes(body, usize::MAX)
+        .await
+        .map_err(|_| AppError::new(StatusCode::PAYLOAD_TOO_LARGE, "Request body is too large"))?;
+    let fingerprint = fingerprint(&parts, &body);
+
+    let existing = state
+        .idempotency
+        .store
+        .begin(&scope, &fingerprint, state.idempotency.lock_timeout)
+        .await;
+    match existing {
+        Ok(Some(record)) if record.fingerprint != fingerprint => Err(AppError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Idempotency-Key has already been used with a different request",
+        )),
+        Ok(Some(Record {
+            response: Some(response),
+            ..
+        })) => Ok(response.into_response()),
+        Ok(Some(_)) => Err(AppError::new(
+            StatusCode::CONFLICT,
+            "A request with this Idempotency-Key is still being processed",
+        )),
+        Ok(None) => {
+            let in_flight = InFlight::new(state.idempotency.store.clone(), &scope);
+            let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+            store_response(&state, &in_flight.finish(), response).await
+        }
+        Err(err) => {
+            eprintln!("Idempotency store error : {:#}", err);
+            Ok(next.run(Request::from_parts(parts, Body::from(body))).await)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::SocketAddr,
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    use axum::{
+        Extension, Router, extract::ConnectInfo, middleware::from_fn_with_state, routing::post,
+    };
+    use axum_test::TestServer;
+    use http::StatusCode;
+    use tokio::time::{sleep, timeout};
+
+    use super::{Idempotency, IdempotencyStore, MemoryIdempotencyStore, idempotency_middleware};
+    use crate::{
+        app::{AppState, router},
+        auth::{LoginRequest, TokenService},
+        model::{Brand, BrandRequest},
+        pagination::Page,
+        repository::MemoryRepository,
+    };
+
+    fn state(idempotency: Idempotency) -> AppState {
+        AppState::new(
+            Arc::new(MemoryRepository::new()),
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        )
+        .with_idempotency(idempotency)
+    }
+
+    fn server(app: Router) -> TestServer {
+        let client = SocketAddr::from(([127, 0, 0, 1], 8080));
+        TestServer::new(app.layer(Extension(ConnectInfo(client)))).unwrap()
+    }
+
+    fn request(name: &str) -> BrandRequest {
+        BrandRequest {
+            name: name.to_string(),
+            description: None,
+        }
+    }
+
+    fn counting_server(idempotency: Idempotency, calls: Arc<AtomicUsize>) -> TestServer {
+        let state = state(idempotency);
+        let app = Router::new()
+            .route(
+                "/orders",
+                post(move || async move {
+                    let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                    sleep(Duration::from_millis(50)).await;
+                    match call {
+                        1 => (StatusCode::SERVICE_UNAVAILABLE, "try again".to_string()),
+                        call => (StatusCode::CREATED, format!("order {}", call)),
+                    }
+                }),
+            )
+            .layer(from_fn_with_state(state.clone(), idempotency_middleware))
+            .with_state(state);
+
+        server(app)
+    }
+
+    #[tokio::test]
+    async fn test_replay_create() {
+        let state = state(Idempotency::default());
+        let token = state.tokens.issue("rizki").unwrap();
+        let server = server(router(state));
+
+        let first = server
+            .post("/api/brands")
+            .add_header("Idempotency-Key", "brand-1")
+            .json(&request("Samsung"))
+            .await;
+        first.assert_status(StatusCode::CREATED);
+        assert!(first.maybe_header("Idempotent-Replayed").is_none());
+
+        let retry = server
+            .post("/api/brands")
+            .add_header("Idempotency-Key", "brand-1")
+            .json(&request("Samsung"))
+            .await;
+        retry.assert_status(StatusCode::CREATED);
+        retry.assert_header("Idempotent-Replayed", "true");
+        retry.assert_header("Content-Type", "application/json");
+        assert_eq!(retry.json::<Brand>(), first.json::<Brand>());
+
+        server
+            .post("/api/brands")
+            .add_header("Idempotency-Key", "brand-1")
+            .authorization_bearer(&token)
+            .json(&request("Samsung"))
+            .await
+            .assert_status(StatusCode::CREATED);
+
+        let brands: Page<Brand> = server.get("/api/brands").await.json();
+        assert_eq!(brands.total, 2);
+    }
+
+    #[tokio::test]
+    async fn test_scope_by_verified_user() {
+        let repository = Arc::new(MemoryRepository::new());
+        repository.add_user("rizki", "rahasia");
+        let state = AppState::new(
+            repository,
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        );
+        let token = state.tokens.issue("rizki").unwrap();
+        let server = server(router(state));
+        let cookie = server
+            .post("/api/sessions")
+            .json(&LoginRequest {
+                username: "rizki".to_string(),
+                password: "rahasia".to_string(),
+            })
+            .await
+            .cookie("session_id");
+
+        let create = || {
+            server
+                .post("/api/brands")
+                .add_header("Idempotency-Key", "brand-1")
+                .json(&request("Samsung"))
+        };
+
+        let anonymous = create().add_header("X-API-Key", "acak").await;
+        anonymous.assert_status(StatusCode::CREATED);
+
+        let user = create().authorization_bearer(&token).await;
+        user.assert_status(StatusCode::CREATED);
+        assert!(user.maybe_header("Idempotent-Replayed").is_none());
+        assert_ne!(user.json::<Brand>().id, anonymous.json::<Brand>().id);
+
+        let retry = create()
+            .authorization_bearer(&token)
+            .add_header("X-API-Key", "acak")
+            .await;
+        retry.assert_header("Idempotent-Replayed", "true");
+        assert_eq!(retry.json::<Brand>(), user.json::<Brand>());
+
+        let retry = create().add_cookie(cookie).await;
+        retry.assert_header("Idempotent-Replayed", "true");
+        assert_eq!(retry.json::<Brand>(), user.json::<Brand>());
+
+        let brands: Page<Brand> = server.get("/api/brands").await.json();
+        assert_eq!(brands.total, 2);
+    }
+
+    #[tokio::test]
+    async fn test_key_reused_with_different_payload() {
+        let server = server(router(state(Idempotency::default())));
+
+        server
+            .post("/api/brands")
+            .add_header("Idempotency-Key", "brand-1")
+            .json(&request("Samsung"))
+            .await
+            .assert_status(StatusCode::CREATED);
+
+        server
+            .post("/api/brands")
+            .add_header("Idempotency-Key", "brand-1")
+            .json(&request("Xiaomi"))
+            .await
+            .assert_status_unprocessable_entity();
+
+        server
+            .post("/api/brands")
+            .add_header("Idempotency-Key", "x".repeat(256))
+            .json(&request("Xiaomi"))
+            .await
+            .assert_status_bad_request();
+
+        let brands: Page<Brand> = server.get("/api/brands").await.json();
+        assert_eq!(brands.total, 1);
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_errors_and_expiry() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let server = counting_server(
+            Idempotency::default().with_ttl(Duration::from_millis(200)),
+            calls.clone(),
+        );
+        let post = || {
+            server
+                .post("/orders")
+                .add_header("Idempotency-Key", "order-1")
+        };
+
+        post().await.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+
+        let (first, second) = tokio::join!(post(), async {
+            sleep(Duration::from_millis(10)).await;
+            post().await
+        });
+        first.assert_status(StatusCode::CREATED);
+        assert_eq!(first.text(), "order 2");
+        second.assert_status(StatusCode::CONFLICT);
+
+        let replay = post().await;
+        replay.assert_header("Idempotent-Replayed", "true");
+        assert_eq!(replay.text(), "order 2");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        sleep(Duration::from_millis(250)).await;
+        assert_eq!(post().await.text(), "order 3");
+    }
+
+    #[tokio::test]
+    async fn test_dropped_handler_is_abandoned() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let server = counting_server(Idempotency::default(), calls.clone());
+        let post = || {
+            server
+                .post("/orders")
+                .add_header("Idempotency-Key", "order-1")
+        };
+
+        assert!(
+            timeout(Duration::from_millis(10), post().into_future())
+                .await
+                .is_err()
+        );
+        sleep(Duration::from_millis(10)).await;
+
+        let response = post().await;
+        response.assert_status(StatusCode::CREATED);
+        assert_eq!(response.text(), "order 2");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_client_and_cookies_are_not_replayed() {
+        let repository = Arc::new(MemoryRepository::new());
+        repository.add_user("rizki", "rahasia");
+        let state = AppState::new(
+            repository,
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        );
+
+        let anonymous = TestServer::new(router(state.clone())).unwrap();
+        for _ in 0..2 {
+            let response = anonymous
+                .post("/api/brands")
+                .add_header("Idempotency-Key", "brand-1")
+                .json(&request("Samsung"))
+                .await;
+            response.assert_status(StatusCode::CREATED);
+            assert!(response.maybe_header("Idempotent-Replayed").is_none());
+        }
+        let brands: Page<Brand> = anonymous.get("/api/brands").await.json();
+        assert_eq!(brands.total, 2);
+
+        let server = server(router(state));
+        let login = || {
+            server
+                .post("/api/sessions")
+                .add_header("Idempotency-Key", "login-1")
+                .json(&LoginRequest {
+                    username: "rizki".to_string(),
+                    password: "rahasia".to_string(),
+                })
+        };
+        let first = login().await;
+        assert!(first.maybe_header("Set-Cookie").is_some());
+
+        let retry = login().await;
+        retry.assert_header("Idempotent-Replayed", "true");
+        assert!(retry.maybe_header("Set-Cookie").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expired_entries_are_swept() {
+        let store = MemoryIdempotencyStore::new().with_sweep_interval(Duration::from_millis(500));
+        let lock_timeout = Duration::from_millis(200);
+
+        for key in 1..=100 {
+            let key = format!("ip:127.0.0.1:{}", key);
+            assert!(
+                store
+                    .begin(&key, "a", lock_timeout)
+                    .await
+                    .unwrap()
+                    .is_none()
+            );
+        }
+        assert!(
+            store
+                .begin("ip:127.0.0.1:1", "a", lock_timeout)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(store.entries(), 100);
+
+        sleep(Duration::from_millis(300)).await;
+        assert!(
+            store
+                .begin("ip:127.0.0.1:1", "b", lock_timeout)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(store.entries(), 100);
+
+        sleep(Duration::from_millis(300)).await;
+        store
+            .begin("ip:127.0.0.1:new", "a", lock_timeout)
+            .await
+            .unwrap();
+        assert_eq!(store.entries(), 1);
+    }
+}
@@ -0,0 +1,494 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{Path, State},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::{Stream, stream};
+use http::HeaderMap;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use utoipa::ToSchema;
+use utoipa_axum::{router::OpenApiRouter, routes};
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    error::{AppError, Problem},
+    shutdown::ShutdownSignal,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct JobEvent {
+    pub sequence: u64,
+    pub status: JobStatus,
+    pub progress: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct JobAccepted {
+    pub id: Uuid,
+    pub events: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct JobSettings {
+    pub replay: usize,
+    pub keep_alive: Duration,
+    pub retention: Duration,
+}
+
+impl Default for JobSettings {
+    fn default() -> Self {
+        Self {
+            replay: 100,
+            keep_alive: Duration::from_secs(15),
+            retention: Duration::from_secs(600),
+        }
+    }
+}
+
+struct JobState {
+    events: VecDeque<JobEvent>,
+    sequence: u64,
+    finished_at: Option<Instant>,
+}
+
+struct Job {
+    state: Mutex<JobState>,
+    latest: watch::Sender<u64>,
+    replay: usize,
+}
+
+impl Job {
+    fn events_after(&self, cursor: u64) -> (Vec<JobEvent>, bool) {
+        let state = self.state.lock().unwrap();
+        let events = state
+            .events
+            .iter()
+            .filter(|event| event.sequence > cursor)
+            .cloned()
+            .collect();
+
+        (events, state.finished_at.is_some())
+    }
+}
+
+pub struct JobHandle {
+    job: Arc<Job>,
+}
+
+impl JobHandle {
+    fn push(&self, status: JobStatus, progress: u8, message: Option<String>) {
+        let mut state = self.job.state.lock().unwrap();
+        if state.finished_at.is_some() {
+            return;
+        }
+
+        state.sequence += 1;
+        let sequence = state.sequence;
+        if state.events.len() == self.job.replay {
+            state.events.pop_front();
+        }
+        state.events.push_back(JobEvent {
+            sequence,
+            status,
+            progress: progress.min(100),
+            message,
+        });
+        if status != JobStatus::Running {
+            state.finished_at = Some(Instant::now());
+        }
+        drop(state);
+
+        self.job.latest.send_replace(sequence);
+    }
+
+    pub fn progress(&self, progress: u8, message: impl Into<String>) {
+        self.push(JobStatus::Running, progress, Some(message.into()));
+    }
+
+    pub fn complete(&self, message: impl Into<String>) {
+        self.push(JobStatus::Completed, 100, Some(message.into()));
+    }
+
+    pub fn fail(&self, progress: u8, message: impl Into<String>) {
+        self.push(JobStatus::Failed, progress, Some(message.into()));
+    }
+}
+
+impl Drop for JobHandle {
+    fn drop(&mut self) {
+        let progress = self
+            .job
+            .state
+            .lock()
+            .unwrap()
+            .events
+            .back()
+            .map(|event| event.progress)
+            .unwrap_or(0);
+        self.fail(progress, "Job stopped without finishing");
+    }
+}
+
+pub struct JobRegistry {
+    settings: JobSettings,
+    jobs: Mutex<HashMap<Uuid, Arc<Job>>>,
+}
+
+impl Default for JobRegistry {
+    fn default() -> Self {
+        Self::new(JobSettings::default())
+    }
+}
+
+impl JobRegistry {
+    pub fn new(settings: JobSettings) -> Self {
+        Self {
+            settings,
+            jobs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn settings(&self) -> &JobSettings {
+        &self.settings
+    }
+
+    pub fn create(&self) -> (Uuid, JobHandle) {
+        let id = Uuid::now_v7();
+        let job = Arc::new(Job {
+            state: Mutex::new(JobState {
+                events: VecDeque::with_capacity(self.settings.replay),
+                sequence: 0,
+                finished_at: None,
+            }),
+            latest: watch::channel(0).0,
+            replay: self.settings.replay.max(1),
+        });
+
+        let mut jobs = self.jobs.lock().unwrap();
+        let retention = self.settings.retention;
+        jobs.retain(|_, job| {
+            job.state
+                .lock()
+                .unwrap()
+                .finished_at
+                .is_none_or(|finished_at| finished_at.elapsed() < retention)
+        });
+        jobs.insert(id, job.clone());
+
+        (id, JobHandle { job })
+    }
+
+    fn get(&self, id: Uuid) -> Option<Arc<Job>> {
+        self.jobs.lock().unwrap().get(&id).cloned()
+    }
+}
+
+fn last_event_id(headers: &HeaderMap) -> u64 {
+    headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+fn to_sse(event: &JobEvent) -> Event {
+    let name = match event.status {
+        JobStatus::Running => "progress",
+        JobStatus::Completed => "completed",
+        JobStatus::Failed => "failed",
+    };
+
+    Event::default()
+        .id(event.sequence.to_string())
+        .event(name)
+        .json_data(event)
+        .unwrap_or_else(|_| Event::default().comment("unserializable event"))
+}
+
+fn job_stream(
+    job: Arc<Job>,
+    cursor: u64,
+    shutdown: ShutdownSignal,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    let latest = job.latest.subscribe();
+
+    stream::unfold(
+        (
+            job,
+            latest,
+            shutdown,
+            cursor,
+            VecDeque::<JobEvent>::new(),
+            false,
+        ),
+        |(job, mut latest, mut shutdown, mut cursor, mut pending, mut finished)| async move {
+            loop {
+                if let Some(event) = pending.pop_front() {
+                    cursor = event.sequence;
+                    let sse = to_sse(&event);
+                    return Some((Ok(sse), (job, latest, shutdown, cursor, pending, finished)));
+                }
+                if finished {
+                    return None;
+                }
+
+                latest.borrow_and_update();
+                let (events, done) = job.events_after(cursor);
+                if events.is_empty() && !done {
+                    tokio::select! {
+                        changed = latest.changed() => if changed.is_err() {
+                            return None;
+                        },
+                        _ = shutdown.wait() => return None,
+                    }
+                    continue;
+                }
+
+                pending.extend(events);
+                finished = done;
+            }
+        },
+    )
+}
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(events))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/events",
+    tag = "jobs",
+    params(
+        ("id" = Uuid, Path, description = "Job id"),
+        ("Last-Event-ID" = Option<u64>, Header, description = "Resume after this event")
+    ),
+    responses(
+        (status = 200, description = "Stream of job progress events", body = JobEvent, content_type = "text/event-stream"),
+        (status = 404, description = "Job is not found", body = Problem, content_type = "application/problem+json")
+    )
+)]
+async fn events(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    let job = state
+        .jobs
+        .get(id)
+        .ok_or_else(|| AppError::not_found(format!("Job {} is not found", id)))?;
+
+    Ok(Sse::new(job_stream(
+        job,
+        last_event_id(&headers),
+        state.shutdown.signal(),
+    ))
+    .keep_alive(
+        KeepAlive::new()
+            .interval(state.jobs.settings().keep_alive)
+            .text("keep-alive"),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use axum_test::TestServer;
+    use http::StatusCode;
+    use tokio::time::{sleep, timeout};
+
+    use super::{JobAccepted, JobRegistry, JobSettings};
+    use crate::{
+        app::{AppState, router},
+        auth::TokenService,
+        error::Problem,
+        model::{Brand, BrandRequest},
+        pagination::Page,
+        repository::MemoryRepository,
+    };
+
+    fn server(settings: JobSettings) -> (TestServer, AppState) {
+        let state = AppState::new(
+            Arc::new(MemoryRepository::new()),
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        )
+        .with_jobs(JobRegistry::new(settings));
+
+        (TestServer::new(router(state.clone())).unwrap(), state)
+    }
+
+    fn ids(text: &str) -> Vec<&str> {
+        text.lines()
+            .filter_map(|line| line.strip_prefix("id: "))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_stream_and_resume() {
+        let (server, state) = server(JobSettings::default());
+        let (id, job) = state.jobs.create();
+        job.progress(30, "first");
+        job.progress(60, "second");
+        job.complete("done");
+
+        let response = server.get(&format!("/api/jobs/{}/events", id)).await;
+        response.assert_status_ok();
+        response.assert_header("Content-Type", "text/event-stream");
+        let text = response.text();
+        assert_eq!(ids(&text), vec!["1", "2", "3"]);
+        assert!(text.contains("event: progress"));
+        assert!(text.contains("event: completed"));
+        assert!(text.contains(r#""progress":60"#));
+
+        let response = server
+            .get(&format!("/api/jobs/{}/events", id))
+            .add_header("Last-Event-ID", "2")
+            .await;
+        assert_eq!(ids(&response.text()), vec!["3"]);
+
+        let response = server
+            .get(&format!("/api/jobs/{}/events", id))
+            .add_header("Last-Event-ID", "3")
+            .await;
+        response.assert_status_ok();
+        assert!(response.text().is_empty());
+
+        server
+            .get(&format!("/api/jobs/{}/events", uuid::Uuid::now_v7()))
+            .await
+            .assert_status_not_found();
+    }
+
+    #[tokio::test]
+    async fn test_bounded_replay_and_keep_alive() {
+        let (server, state) = server(JobSettings {
+            replay: 2,
+            keep_alive: Duration::from_millis(20),
+            ..JobSettings::default()
+        });
+        let (id, job) = state.jobs.create();
+        for progress in [10, 20, 30] {
+            job.progress(progress, "working");
+        }
+
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(100)).await;
+            job.progress(90, "almost");
+            drop(job);
+        });
+
+        let text = server.get(&format!("/api/jobs/{}/events", id)).await.text();
+        assert_eq!(ids(&text), vec!["2", "3", "4", "5"]);
+        assert!(text.contains(": keep-alive"));
+        assert!(text.contains("event: failed"));
+        assert!(text.contains("Job stopped without finishing"));
+    }
+
+    #[tokio::test]
+    async fn test_import_brands() {
+        let (server, _) = server(JobSettings::default());
+        let requests: Vec<BrandRequest> = ["Samsung", "Apple", "Xiaomi"]
+            .into_iter()
+            .map(|name| BrandRequest {
+                name: name.to_string(),
+                description: None,
+            })
+            .collect();
+
+        let response = server.post("/api/brands/import").json(&requests).await;
+        response.assert_status(StatusCode::ACCEPTED);
+        let accepted: JobAccepted = response.json();
+        response.assert_header("Location", accepted.events.as_str());
+
+        let text = server.get(&accepted.events).await.text();
+        assert!(text.contains("Imported brand Xiaomi"));
+        assert!(text.contains(r#""status":"completed""#));
+
+        let brands: Page<Brand> = server.get("/api/brands").await.json();
+        assert_eq!(brands.total, 3);
+
+        let response = server
+            .post("/api/brands/import")
+            .json(&vec![
+                BrandRequest {
+                    name: "Oppo".to_string(),
+                    description: None,
+                },
+                BrandRequest {
+                    name: " ".to_string(),
+                    description: None,
+                },
+            ])
+            .await;
+        response.assert_status_unprocessable_entity();
+        let problem: Problem = response.json();
+        let errors = problem.extensions["errors"].as_object().unwrap();
+        assert_eq!(errors.keys().collect::<Vec<_>>(), vec!["1.name"]);
+    }
+
+    #[tokio::test]
+    async fn test_import_stops_on_shutdown() {
+        let (server, state) = server(JobSettings::default());
+        state.shutdown.trigger();
+
+        let response = server
+            .post("/api/brands/import")
+            .json(&vec![BrandRequest {
+                name: "Samsung".to_string(),
+                description: None,
+            }])
+            .await;
+        response.assert_status(StatusCode::ACCEPTED);
+        let accepted: JobAccepted = response.json();
+        sleep(Duration::from_millis(50)).await;
+
+        let text = server.get(&accepted.events).await.text();
+        assert!(text.contains("event: failed"));
+        assert!(text.contains("Import stopped by shutdown after 0 of 1 brands"));
+
+        let brands: Page<Brand> = server.get("/api/brands").await.json();
+        assert_eq!(brands.total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_stream_ends_on_shutdown() {
+        let (server, state) = server(JobSettings::default());
+        let (id, job) = state.jobs.create();
+        job.progress(10, "started");
+
+        let shutdown = state.shutdown.clone();
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(100)).await;
+            shutdown.trigger();
+        });
+
+        let text = timeout(
+            Duration::from_secs(3),
+            server.get(&format!("/api/jobs/{}/events", id)),
+        )
+        .await
+        .unwrap()
+        .text();
+        assert_eq!(ids(&text), vec!["1"]);
+        assert!(!text.contains("event: failed"));
+    }
+}
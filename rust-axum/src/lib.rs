@@ -0,0 +1,29 @@
+pub mod access_log;
+pub mod app;
+pub mod assets;
+pub mod auth;
+pub mod brand;
+pub mod category;
+pub mod conditional;
+pub mod config;
+pub mod error;
+pub mod extract;
+pub mod health;
+pub mod idempotency;
+pub mod jobs;
+pub mod live;
+pub mod metrics;
+pub mod model;
+pub mod negotiate;
+pub mod openapi;
+pub mod pagination;
+pub mod rate_limit;
+pub mod repository;
+pub mod request_id;
+pub mod security;
+pub mod session;
+pub mod shutdown;
+pub mod timeout;
+pub mod upload;
+pub mod validation;
+pub mod view;
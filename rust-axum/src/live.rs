@@ -0,0 +1,652 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use axum::{
+    extract::{
+        State,
+        ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade, close_code},
+    },
+    response::Response,
+};
+use http::{HeaderMap, header::SEC_WEBSOCKET_PROTOCOL};
+use jsonwebtoken::get_current_timestamp;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{
+    sync::mpsc,
+    time::{Instant, MissedTickBehavior, interval, sleep, timeout},
+};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{
+    app::AppState,
+    auth,
+    error::{AppError, Problem},
+    shutdown::ShutdownSignal,
+};
+
+pub const BEARER_PROTOCOL: &str = "bearer";
+const ENTITIES: &[&str] = &["brand", "category"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventAction {
+    Created,
+    Updated,
+    Deleted,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    pub topic: String,
+    pub action: EventAction,
+    pub data: Value,
+}
+
+impl Event {
+    pub fn new<T: Serialize>(entity: &str, id: i32, action: EventAction, data: &T) -> Self {
+        Self {
+            topic: format!("{}:{}", entity, id),
+            action,
+            data: serde_json::to_value(data).unwrap_or(Value::Null),
+        }
+    }
+}
+
+pub fn parse_topic(topic: &str) -> Result<String, String> {
+    let (entity, id) = topic
+        .split_once(':')
+        .ok_or_else(|| format!("Topic `{}` must look like `entity:id`", topic))?;
+
+    if !ENTITIES.contains(&entity) {
+        return Err(format!(
+            "Unknown entity `{}`, expected one of {}",
+            entity,
+            ENTITIES.join(", ")
+        ));
+    }
+    if id != "*" && id.parse::<i32>().is_err() {
+        return Err(format!("Topic id `{}` must be a number or `*`", id));
+    }
+
+    Ok(topic.to_string())
+}
+
+fn topic_matches(pattern: &str, topic: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => topic.starts_with(prefix),
+        None => pattern == topic,
+    }
+}
+
+struct Subscriber {
+    topics: HashSet<String>,
+    sender: mpsc::Sender<Event>,
+}
+
+pub struct EventBus {
+    buffer: usize,
+    next_id: AtomicU64,
+    subscribers: Mutex<HashMap<u64, Subscriber>>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
+
+impl EventBus {
+    pub fn new(buffer: usize) -> Self {
+        Self {
+            buffer: buffer.max(1),
+            next_id: AtomicU64::new(1),
+            subscribers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn register(self: &Arc<Self>) -> (Subscription, mpsc::Receiver<Event>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = mpsc::channel(self.buffer);
+
+        self.subscribers.lock().unwrap().insert(
+            id,
+            Subscriber {
+                topics: HashSet::new(),
+                sender,
+            },
+        );
+
+        (
+            Subscription {
+                id,
+                bus: self.clone(),
+            },
+            receiver,
+        )
+    }
+
+    pub fn publish(&self, event: Event) {
+        self.subscribers.lock().unwrap().retain(|id, subscriber| {
+            if !subscriber
+                .topics
+                .iter()
+                .any(|pattern| topic_matches(pattern, &event.topic))
+            {
+                return true;
+            }
+
+            match subscriber.sender.try_send(event.clone()) {
+                Ok(()) => true,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    eprintln!("Dropping live subscriber {} : send buffer is full", id);
+                    false
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => false,
+            }
+        });
+    }
+
+    pub fn subscribers(&self) -> usize {
+        self.subscribers.lock().unwrap().len()
+    }
+}
+
+pub struct Subscription {
+    id: u64,
+    bus: Arc<EventBus>,
+}
+
+impl Subscription {
+    fn update(&self, change: impl FnOnce(&mut HashSet<String>)) {
+        if let Some(subscriber) = self.bus.subscribers.lock().unwrap().get_mut(&self.id) {
+            change(&mut subscriber.topics);
+        }
+    }
+
+    pub fn subscribe(&self, topic: &str) {
+        self.update(|topics| {
+            topics.insert(topic.to_string());
+        });
+    }
+
+    pub fn unsubscribe(&self, topic: &str) {
+        self.update(|topics| {
+            topics.remove(topic);
+        });
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.bus.subscribers.lock().unwrap().remove(&self.id);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LiveSettings {
+    pub heartbeat_interval: Duration,
+    pub idle_timeout: Duration,
+}
+
+impl Default for LiveSettings {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval: Duration::from_secs(30),
+            idle_timeout: Duration::from_secs(90),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+enum ClientMessage {
+    Subscribe { topic: String },
+    Unsubscribe { topic: String },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ServerMessage {
+    Subscribed { topic: String },
+    Unsubscribed { topic: String },
+    Event(Event),
+    Error { message: String },
+}
+
+impl ServerMessage {
+    fn into_message(self) -> Message {
+        Message::Text(serde_json::to_string(&self).unwrap_or_default().into())
+    }
+}
+
+fn handle_command(subscription: &Subscription, text: &str) -> ServerMessage {
+    let command = match serde_json::from_str::<ClientMessage>(text) {
+        Ok(command) => command,
+        Err(err) => {
+            return ServerMessage::Error {
+                message: format!("Invalid message : {}", err),
+            };
+        }
+    };
+
+    match command {
+        ClientMessage::Subscribe { topic } => match parse_topic(&topic) {
+            Ok(topic) => {
+                subscription.subscribe(&topic);
+                ServerMessage::Subscribed { topic }
+            }
+            Err(message) => ServerMessage::Error { message },
+        },
+        ClientMessage::Unsubscribe { topic } => {
+            subscription.unsubscribe(&topic);
+            ServerMessage::Unsubscribed { topic }
+        }
+    }
+}
+
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+async fn close(mut socket: WebSocket, code: u16, reason: &'static str) {
+    let frame = Message::Close(Some(CloseFrame {
+        code,
+        reason: reason.into(),
+    }));
+    if socket.send(frame).await.is_err() {
+        return;
+    }
+
+    let _ = timeout(CLOSE_TIMEOUT, async {
+        while let Some(Ok(message)) = socket.recv().await {
+            if matches!(message, Message::Close(_)) {
+                break;
+            }
+        }
+    })
+    .await;
+}
+
+async fn connection(
+    mut socket: WebSocket,
+    bus: Arc<EventBus>,
+    settings: LiveSettings,
+    expires_in: Duration,
+    mut shutdown: ShutdownSignal,
+) {
+    let (subscription, mut events) = bus.register();
+    let expiry = sleep(expires_in);
+    tokio::pin!(expiry);
+    let mut heartbeat = interval(settings.heartbeat_interval);
+    heartbeat.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    heartbeat.tick().await;
+    let mut last_seen = Instant::now();
+
+    loop {
+        let outgoing = tokio::select! {
+            message = socket.recv() => {
+                last_seen = Instant::now();
+                match message {
+                    Some(Ok(Message::Text(text))) => handle_command(&subscription, &text).into_message(),
+                    Some(Ok(Message::Binary(_))) => ServerMessage::Error {
+                        message: "Binary messages are not supported".to_string(),
+                    }
+                    .into_message(),
+                    Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                }
+            }
+            event = events.recv() => match event {
+                Some(event) => ServerMessage::Event(event).into_message(),
+                None => {
+                    return close(socket, close_code::POLICY, "Send buffer is full").await;
+                }
+            },
+            _ = &mut expiry => {
+                return close(socket, close_code::POLICY, "Token has expired").await;
+            }
+            _ = shutdown.wait() => {
+                return close(socket, close_code::AWAY, "Server is shutting down").await;
+            }
+            _ = heartbeat.tick() => {
+                if last_seen.elapsed() > settings.idle_timeout {
+                    return close(socket, close_code::AWAY, "Heartbeat timed out").await;
+                }
+                Message::Ping(Default::default())
+            }
+        };
+
+        if socket.send(outgoing).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Browsers can not set headers on a WebSocket handshake, so they send the token
+/// as the subprotocol that follows `bearer`, e.g. `new WebSocket(url, ["bearer", token])`.
+fn protocol_token(headers: &HeaderMap) -> Option<&str> {
+    let mut protocols = headers
+        .get(SEC_WEBSOCKET_PROTOCOL)?
+        .to_str()
+        .ok()?
+        .split(',')
+        .map(str::trim);
+    protocols.position(|protocol| protocol == BEARER_PROTOCOL)?;
+    protocols.next()
+}
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(live))
+}
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "live",
+    security(("bearer" = [])),
+    params(("Sec-WebSocket-Protocol" = Option<String>, Header, description = "`bearer, <token>` for clients that can not set the Authorization header")),
+    responses(
+        (status = 101, description = "Switching to the WebSocket protocol"),
+        (status = 401, description = "Bearer token is missing or invalid", body = Problem, content_type = "application/problem+json")
+    )
+)]
+async fn live(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, AppError> {
+    let token = auth::bearer_token(&headers)
+        .or_else(|| protocol_token(&headers))
+        .ok_or_else(|| AppError::unauthorized("Missing bearer token"))?;
+    let claims = state
+        .tokens
+        .verify(token)
+        .map_err(|_| AppError::unauthorized("Invalid or expired token"))?;
+
+    let bus = state.events.clone();
+    let settings = state.live_settings.clone();
+    let expires_in = Duration::from_secs(claims.exp.saturating_sub(get_current_timestamp()));
+    let shutdown = state.shutdown.signal();
+    Ok(upgrade
+        .protocols([BEARER_PROTOCOL])
+        .on_upgrade(move |socket| connection(socket, bus, settings, expires_in, shutdown)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use axum_test::TestServer;
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::{Value, json};
+    use tokio::{
+        net::TcpListener,
+        sync::oneshot,
+        time::{sleep, timeout},
+    };
+    use tokio_tungstenite::{
+        MaybeTlsStream, WebSocketStream, connect_async,
+        tungstenite::{
+            Error, Message, client::IntoClientRequest, protocol::frame::coding::CloseCode,
+        },
+    };
+
+    use super::{Event, EventAction, EventBus, LiveSettings};
+    use crate::{
+        app::{AppState, router},
+        auth::TokenService,
+        model::{BrandRequest, CategoryRequest},
+        repository::MemoryRepository,
+        shutdown::serve_with_shutdown,
+    };
+
+    type Client = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+    async fn start(settings: LiveSettings) -> (String, TestServer, String) {
+        let state = AppState::new(
+            Arc::new(MemoryRepository::new()),
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        )
+        .with_live(EventBus::new(8), settings);
+        let token = state.tokens.issue("rizki").unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = router(state.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        (
+            format!("ws://{}/api/live", address),
+            TestServer::new(router(state)).unwrap(),
+            token,
+        )
+    }
+
+    async fn connect(url: &str, token: &str) -> Client {
+        let mut request = url.into_client_request().unwrap();
+        request.headers_mut().insert(
+            "Sec-WebSocket-Protocol",
+            format!("bearer, {}", token).parse().unwrap(),
+        );
+        let (client, response) = connect_async(request).await.unwrap();
+        assert_eq!(response.headers()["Sec-WebSocket-Protocol"], "bearer");
+        client
+    }
+
+    async fn next_close(client: &mut Client) -> CloseCode {
+        loop {
+            match client.next().await.unwrap().unwrap() {
+                Message::Close(frame) => return frame.unwrap().code,
+                _ => continue,
+            }
+        }
+    }
+
+    async fn next_json(client: &mut Client) -> Value {
+        loop {
+            match client.next().await.unwrap().unwrap() {
+                Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+                Message::Ping(_) | Message::Pong(_) => continue,
+                message => panic!("unexpected message {:?}", message),
+            }
+        }
+    }
+
+    async fn send(client: &mut Client, value: Value) {
+        client
+            .send(Message::Text(value.to_string().into()))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_requires_token() {
+        let (url, _, token) = start(LiveSettings::default()).await;
+
+        match connect_async(url.as_str()).await {
+            Err(Error::Http(response)) => assert_eq!(response.status(), 401),
+            other => panic!("expected 401, got {:?}", other.map(|_| ())),
+        }
+        match connect_async(format!("{}?token={}", url, token)).await {
+            Err(Error::Http(response)) => assert_eq!(response.status(), 401),
+            other => panic!("expected 401, got {:?}", other.map(|_| ())),
+        }
+        let mut request = url.as_str().into_client_request().unwrap();
+        request
+            .headers_mut()
+            .insert("Sec-WebSocket-Protocol", "bearer, salah".parse().unwrap());
+        match connect_async(request).await {
+            Err(Error::Http(response)) => assert_eq!(response.status(), 401),
+            other => panic!("expected 401, got {:?}", other.map(|_| ())),
+        }
+        connect(&url, &token).await;
+
+        let mut request = url.into_client_request().unwrap();
+        request.headers_mut().insert(
+            "Authorization",
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        assert!(connect_async(request).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_and_receive_events() {
+        let (url, server, token) = start(LiveSettings::default()).await;
+        let mut client = connect(&url, &token).await;
+
+        send(
+            &mut client,
+            json!({ "action": "subscribe", "topic": "brand:*" }),
+        )
+        .await;
+        assert_eq!(
+            next_json(&mut client).await,
+            json!({ "type": "subscribed", "topic": "brand:*" })
+        );
+        send(
+            &mut client,
+            json!({ "action": "subscribe", "topic": "category:2" }),
+        )
+        .await;
+        next_json(&mut client).await;
+        send(
+            &mut client,
+            json!({ "action": "subscribe", "topic": "order:1" }),
+        )
+        .await;
+        let error = next_json(&mut client).await;
+        assert_eq!(error["type"], "error");
+        assert!(error["message"].as_str().unwrap().contains("order"));
+
+        server
+            .post("/api/brands")
+            .json(&BrandRequest {
+                name: "Samsung".to_string(),
+                description: None,
+            })
+            .await;
+        let event = next_json(&mut client).await;
+        assert_eq!(event["type"], "event");
+        assert_eq!(event["topic"], "brand:1");
+        assert_eq!(event["action"], "created");
+        assert_eq!(event["data"]["name"], "Samsung");
+
+        for name in ["Gadget", "Laptop"] {
+            server
+                .post("/api/categories")
+                .json(&CategoryRequest {
+                    name: name.to_string(),
+                    description: None,
+                })
+                .await;
+        }
+        let event = next_json(&mut client).await;
+        assert_eq!(event["topic"], "category:2");
+        assert_eq!(event["data"]["name"], "Laptop");
+
+        send(
+            &mut client,
+            json!({ "action": "unsubscribe", "topic": "brand:*" }),
+        )
+        .await;
+        assert_eq!(next_json(&mut client).await["type"], "unsubscribed");
+        server.delete("/api/brands/1").await;
+        server.delete("/api/categories/2").await;
+        let event = next_json(&mut client).await;
+        assert_eq!(event["topic"], "category:2");
+        assert_eq!(event["action"], "deleted");
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_and_idle_timeout() {
+        let (url, _, token) = start(LiveSettings {
+            heartbeat_interval: Duration::from_millis(50),
+            idle_timeout: Duration::from_millis(120),
+        })
+        .await;
+        let mut client = connect(&url, &token).await;
+
+        assert!(matches!(
+            client.next().await.unwrap().unwrap(),
+            Message::Ping(_)
+        ));
+
+        sleep(Duration::from_millis(300)).await;
+        assert_eq!(next_close(&mut client).await, CloseCode::Away);
+    }
+
+    #[tokio::test]
+    async fn test_close_when_token_expires() {
+        let (url, _, _) = start(LiveSettings::default()).await;
+        let token = TokenService::new(b"secret", Duration::from_secs(1))
+            .issue("rizki")
+            .unwrap();
+        let mut client = connect(&url, &token).await;
+
+        let code = timeout(Duration::from_secs(3), next_close(&mut client))
+            .await
+            .unwrap();
+        assert_eq!(code, CloseCode::Policy);
+    }
+
+    #[tokio::test]
+    async fn test_close_on_shutdown() {
+        let state = AppState::new(
+            Arc::new(MemoryRepository::new()),
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        );
+        let token = state.tokens.issue("rizki").unwrap();
+        let shutdown = state.shutdown.clone();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (trigger, signal) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve_with_shutdown(
+            listener,
+            router(state),
+            async move {
+                let _ = signal.await;
+                shutdown.trigger();
+            },
+            Duration::from_secs(5),
+        ));
+
+        let mut client = connect(&format!("ws://{}/api/live", address), &token).await;
+        trigger.send(()).unwrap();
+
+        let code = timeout(Duration::from_secs(3), next_close(&mut client))
+            .await
+            .unwrap();
+        assert_eq!(code, CloseCode::Away);
+        drop(client);
+
+        timeout(Duration::from_secs(3), server)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_slow_consumer_is_dropped() {
+        let bus = Arc::new(EventBus::new(2));
+        let (subscription, mut events) = bus.register();
+        subscription.subscribe("brand:*");
+        let (_idle, _) = bus.register();
+        assert_eq!(bus.subscribers(), 2);
+
+        for id in 1..=3 {
+            bus.publish(Event::new("brand", id, EventAction::Updated, &id));
+        }
+        assert_eq!(bus.subscribers(), 1);
+
+        assert_eq!(events.recv().await.unwrap().topic, "brand:1");
+        assert_eq!(events.recv().await.unwrap().topic, "brand:2");
+        assert!(events.recv().await.is_none());
+    }
+}
@@ -1,32 +1,136 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{process, sync::Arc};
 
+use tokio::net::TcpListener;
+
+use rust_axum::{
+    access_log::StdoutSink,
+    app::{AppState, router},
+    config::AppConfig,
+    health::{DatabaseCheck, TemplateCheck},
+    metrics::Metrics,
+    repository::PostgresRepository,
+    session::cleanup_expired_sessions,
+    shutdown::{BackgroundTasks, Shutdown, serve_with_shutdown, shutdown_signal},
+    view,
+};
+
+#[cfg(test)]
+use std::collections::HashMap;
+
+#[cfg(test)]
 use anyhow::anyhow;
+#[cfg(test)]
 use axum::{
     Extension, Form, Json, Router,
     body::{Body, Bytes},
     error_handling::HandleError,
     extract::{Multipart, Path, Query, Request, State, rejection::JsonRejection},
-    middleware::{Next, from_fn, map_request},
-    response::{IntoResponse, Response},
+    middleware::{from_fn, from_fn_with_state},
+    response::Response,
     routing::{get, post},
-    serve,
 };
+#[cfg(test)]
 use axum_extra::extract::{CookieJar, cookie::Cookie};
+#[cfg(test)]
 use axum_test::{
     TestServer,
     multipart::{MultipartForm, Part},
 };
+#[cfg(test)]
 use http::{HeaderMap, HeaderValue, Method, StatusCode, Uri};
-use serde::{Deserialize, Serialize};
-use tokio::net::TcpListener;
+
+#[cfg(test)]
+use rust_axum::{
+    access_log::{AccessLogSink, MemorySink, log_middleware},
+    auth::{LoginRequest, LoginResponse},
+    error::{AppError, Problem},
+    request_id::request_id_middleware,
+};
 
 #[tokio::main]
 async fn main() {
-    let app = Router::new().route("/", get(|| async { "Hello, World!" }));
-
-    let listener = TcpListener::bind("0.0.0.0:3000").await.unwrap();
-
-    serve(listener, app).await.unwrap();
+    let config = AppConfig::load("application.toml").unwrap_or_else(|err| {
+        eprintln!("Failed to load configuration : {}", err);
+        process::exit(1);
+    });
+
+    let pool = config.database.connect().await.unwrap_or_else(|err| {
+        eprintln!(
+            "Failed to connect to database {}:{}/{} : {}",
+            config.database.host, config.database.port, config.database.name, err
+        );
+        process::exit(1);
+    });
+
+    let templates = Arc::new(config.templates.load().unwrap_or_else(|err| {
+        eprintln!("Failed to load templates : {:#}", err);
+        process::exit(1);
+    }));
+
+    let mut health = config.health.checks();
+    health.register(Arc::new(DatabaseCheck::new(
+        pool.clone(),
+        config.health.degraded_after(),
+    )));
+    health.register(Arc::new(TemplateCheck::new(
+        templates.clone(),
+        &view::REQUIRED,
+    )));
+
+    let metrics = Arc::new(Metrics::new().with_pool(pool.clone()));
+
+    let shutdown = Shutdown::new();
+    let state = AppState::new(
+        Arc::new(PostgresRepository::new(pool.clone()).with_metrics(metrics.clone())),
+        config.auth.token_service(),
+    )
+    .with_api_keys(config.auth.api_keys())
+    .with_access_log(Arc::new(StdoutSink::new(config.log.format)))
+    .with_storage(Arc::new(config.upload.storage()), config.upload.limits())
+    .with_sessions(config.session.key(), config.session.settings())
+    .with_health_checks(health)
+    .with_metrics(metrics)
+    .with_rate_limiter(config.rate_limit.limiter())
+    .with_security(config.security.clone())
+    .with_live(config.live.events(), config.live.settings())
+    .with_jobs(config.jobs.registry())
+    .with_templates(templates)
+    .with_assets(config.assets.settings())
+    .with_idempotency(config.idempotency.idempotency())
+    .with_timeouts(config.timeout.timeouts())
+    .with_shutdown(shutdown.clone());
+
+    let mut tasks = BackgroundTasks::new();
+    let sessions = state.sessions.clone();
+    let settings = state.session_settings.clone();
+    let period = config.session.cleanup_interval();
+    tasks.spawn("session-cleanup", move |signal| {
+        cleanup_expired_sessions(sessions, settings, period, signal)
+    });
+
+    let app = router(state);
+
+    let listener = TcpListener::bind(config.server.address())
+        .await
+        .unwrap_or_else(|err| {
+            eprintln!("Failed to listen on {} : {}", config.server.address(), err);
+            process::exit(1);
+        });
+
+    serve_with_shutdown(
+        listener,
+        app,
+        async move {
+            shutdown_signal().await;
+            shutdown.trigger();
+        },
+        config.server.shutdown_timeout(),
+    )
+    .await
+    .unwrap();
+
+    tasks.close(config.server.shutdown_timeout()).await;
+    pool.close().await;
 }
 
 #[tokio::test]
@@ -163,12 +267,6 @@ async fn test_body_string() {
     response.assert_text("Body This is body");
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct LoginRequest {
-    username: String,
-    password: String,
-}
-
 #[tokio::test]
 async fn test_body_json() {
     async fn route(payload: Result<Json<LoginRequest>, JsonRejection>) -> String {
@@ -216,11 +314,6 @@ async fn test_response() {
     response.assert_header("X-Owner", "Rizki");
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct LoginResponse {
-    token: String,
-}
-
 #[tokio::test]
 async fn test_response_json() {
     async fn route() -> Json<LoginResponse> {
@@ -300,7 +393,7 @@ async fn test_multipart() {
             }
         }
 
-        assert!(profile.len() > 0);
+        assert!(!profile.is_empty());
         format!("Hello {}", username)
     }
 
@@ -354,25 +447,6 @@ async fn test_cookie_request() {
     response.assert_text("Hello rizki");
 }
 
-async fn log_middleware(request: Request, next: Next) -> Response {
-    println!("Receive request {} {}", request.method(), request.uri());
-
-    let response = next.run(request).await;
-    println!("Send response {}", response.status());
-
-    response
-}
-
-async fn request_id_middleware<T>(mut request: Request<T>) -> Request<T> {
-    let request_id = "random-id";
-
-    request
-        .headers_mut()
-        .insert("X-Request-ID", request_id.parse().unwrap());
-
-    request
-}
-
 #[tokio::test]
 async fn test_middleware() {
     async fn route(method: Method, header_map: HeaderMap) -> String {
@@ -380,30 +454,26 @@ async fn test_middleware() {
         format!("Hello {} {}", method, request_id)
     }
 
+    let memory = Arc::new(MemorySink::default());
+    let sink: Arc<dyn AccessLogSink> = memory.clone();
+
     let app = Router::new()
         .route("/get", get(route))
-        .layer(map_request(request_id_middleware))
-        .layer(from_fn(log_middleware));
+        .layer(from_fn_with_state(sink.clone(), log_middleware))
+        .layer(from_fn(request_id_middleware));
     let server = TestServer::new(app).unwrap();
 
     let response = server.get("/get").await;
     response.assert_status_ok();
-    response.assert_text("Hello GET random-id");
-}
-
-struct AppError {
-    code: i32,
-    message: String,
-}
-
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        (
-            StatusCode::from_u16(self.code as u16).unwrap(),
-            self.message,
-        )
-            .into_response()
-    }
+    let request_id = response.header("X-Request-ID");
+    response.assert_text(format!("Hello GET {}", request_id.to_str().unwrap()));
+
+    let records = memory.records();
+    assert_eq!(records[0].route.as_deref(), Some("/get"));
+    assert_eq!(
+        records[0].request_id.as_deref(),
+        Some(request_id.to_str().unwrap())
+    );
 }
 
 #[tokio::test]
@@ -412,10 +482,7 @@ async fn test_error_handling() {
         if method == Method::POST {
             Ok("Ok".to_string())
         } else {
-            Err(AppError {
-                code: 400,
-                message: "Ups error".to_string(),
-            })
+            Err(AppError::new(StatusCode::BAD_REQUEST, "Ups error"))
         }
     }
 
@@ -426,7 +493,10 @@ async fn test_error_handling() {
 
     let response = server.get("/get").await;
     response.assert_status_bad_request();
-    response.assert_text("Ups error");
+    response.assert_header("Content-Type", "application/problem+json");
+    let problem: Problem = response.json();
+    assert_eq!(problem.title, "Bad Request");
+    assert_eq!(problem.detail, "Ups error");
 
     let response = server.post("/post").await;
     response.assert_status_ok();
@@ -460,6 +530,7 @@ async fn test_unexpected_error() {
     response.assert_text("Error : Method is not allowed");
 }
 
+#[cfg(test)]
 struct DatabaseConfig {
     total: i32,
 }
@@ -0,0 +1,306 @@
+use std::{sync::Arc, time::Instant};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http::{HeaderValue, Method, header::CONTENT_TYPE};
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+use sqlx::{Pool, Postgres};
+use tokio::runtime::Handle;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{app::AppState, error::AppError};
+
+pub struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    latency: HistogramVec,
+    in_flight: IntGauge,
+    pool: Option<Pool<Postgres>>,
+    pool_size: IntGauge,
+    pool_idle: IntGauge,
+    pool_max: IntGauge,
+    pool_wait: Histogram,
+    runtime_workers: IntGauge,
+    runtime_alive_tasks: IntGauge,
+    runtime_queue_depth: IntGauge,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn register<C: prometheus::core::Collector + Clone + 'static>(
+    registry: &Registry,
+    collector: C,
+) -> C {
+    registry
+        .register(Box::new(collector.clone()))
+        .expect("metric names must be unique");
+    collector
+}
+
+fn gauge(registry: &Registry, name: &str, help: &str) -> IntGauge {
+    register(registry, IntGauge::new(name, help).unwrap())
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+        let labels = &["method", "route", "status"];
+
+        Self {
+            requests: register(
+                &registry,
+                IntCounterVec::new(
+                    Opts::new("http_requests_total", "Total number of HTTP requests"),
+                    labels,
+                )
+                .unwrap(),
+            ),
+            latency: register(
+                &registry,
+                HistogramVec::new(
+                    HistogramOpts::new(
+                        "http_request_duration_seconds",
+                        "HTTP request latency in seconds",
+                    ),
+                    labels,
+                )
+                .unwrap(),
+            ),
+            in_flight: gauge(
+                &registry,
+                "http_requests_in_flight",
+                "Number of HTTP requests being served",
+            ),
+            pool: None,
+            pool_size: gauge(&registry, "sqlx_pool_size", "Open database connections"),
+            pool_idle: gauge(&registry, "sqlx_pool_idle", "Idle database connections"),
+            pool_max: gauge(
+                &registry,
+                "sqlx_pool_max_connections",
+                "Maximum database connections",
+            ),
+            pool_wait: register(
+                &registry,
+                Histogram::with_opts(HistogramOpts::new(
+                    "sqlx_pool_acquire_wait_seconds",
+                    "Time spent waiting for a database connection in seconds",
+                ))
+                .unwrap(),
+            ),
+            runtime_workers: gauge(&registry, "tokio_workers", "Number of tokio worker threads"),
+            runtime_alive_tasks: gauge(
+                &registry,
+                "tokio_alive_tasks",
+                "Number of alive tokio tasks",
+            ),
+            runtime_queue_depth: gauge(
+                &registry,
+                "tokio_global_queue_depth",
+                "Number of tasks in the tokio global queue",
+            ),
+            registry,
+        }
+    }
+
+    pub fn with_pool(mut self, pool: Pool<Postgres>) -> Self {
+        self.pool_max
+            .set(pool.options().get_max_connections() as i64);
+        self.pool = Some(pool);
+        self
+    }
+
+    pub fn observe_acquire(&self, start: Instant) {
+        self.pool_wait.observe(start.elapsed().as_secs_f64());
+    }
+
+    pub fn render(&self) -> Result<String, prometheus::Error> {
+        if let Some(pool) = &self.pool {
+            self.pool_size.set(pool.size() as i64);
+            self.pool_idle.set(pool.num_idle() as i64);
+        }
+
+        if let Ok(handle) = Handle::try_current() {
+            let runtime = handle.metrics();
+            self.runtime_workers.set(runtime.num_workers() as i64);
+            self.runtime_alive_tasks
+                .set(runtime.num_alive_tasks() as i64);
+            self.runtime_queue_depth
+                .set(runtime.global_queue_depth() as i64);
+        }
+
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+}
+
+struct InFlight<'a>(&'a IntGauge);
+
+impl<'a> InFlight<'a> {
+    fn start(gauge: &'a IntGauge) -> Self {
+        gauge.inc();
+        Self(gauge)
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+/// Clients can send any method token, so only the standard methods get their
+/// own label value.
+fn method_label(method: &Method) -> &'static str {
+    match *method {
+        Method::GET => "GET",
+        Method::HEAD => "HEAD",
+        Method::POST => "POST",
+        Method::PUT => "PUT",
+        Method::DELETE => "DELETE",
+        Method::PATCH => "PATCH",
+        Method::OPTIONS => "OPTIONS",
+        Method::CONNECT => "CONNECT",
+        Method::TRACE => "TRACE",
+        _ => "OTHER",
+    }
+}
+
+pub async fn metrics_middleware(
+    State(metrics): State<Arc<Metrics>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let start = Instant::now();
+    let method = method_label(request.method());
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let in_flight = InFlight::start(&metrics.in_flight);
+    let response = next.run(request).await;
+    drop(in_flight);
+
+    let status = response.status().as_u16().to_string();
+    let labels = [method, route.as_str(), status.as_str()];
+    metrics.requests.with_label_values(&labels).inc();
+    metrics
+        .latency
+        .with_label_values(&labels)
+        .observe(start.elapsed().as_secs_f64());
+
+    response
+}
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(metrics))
+}
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "metrics",
+    responses((status = 200, body = String, content_type = "text/plain"))
+)]
+async fn metrics(State(state): State<AppState>) -> Result<Response, AppError> {
+    let body = state
+        .metrics
+        .render()
+        .map_err(|err| AppError::internal(format!("Failed to render metrics : {}", err)))?;
+
+    let mut response = body.into_response();
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; version=0.0.4; charset=utf-8"),
+    );
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use axum_test::TestServer;
+    use http::Method;
+    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+
+    use super::Metrics;
+    use crate::{
+        app::{AppState, router},
+        auth::TokenService,
+        repository::MemoryRepository,
+    };
+
+    #[tokio::test]
+    async fn test_metrics_endpoint() {
+        let pool = PgPoolOptions::new()
+            .max_connections(7)
+            .connect_lazy_with(PgConnectOptions::new().host("127.0.0.1").port(1));
+        let metrics = Arc::new(Metrics::new().with_pool(pool));
+        let state = AppState::new(
+            Arc::new(MemoryRepository::new()),
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        )
+        .with_metrics(metrics);
+        let server = TestServer::new(router(state)).unwrap();
+
+        server.get("/api/categories").await.assert_status_ok();
+        server
+            .get("/api/categories/1")
+            .await
+            .assert_status_not_found();
+        server
+            .get("/api/categories/2")
+            .await
+            .assert_status_not_found();
+        server.get("/tidak-ada").await.assert_status_not_found();
+        server
+            .method(Method::from_bytes(b"PURGE").unwrap(), "/tidak-ada")
+            .await
+            .assert_status_not_found();
+
+        let response = server.get("/metrics").await;
+        response.assert_status_ok();
+        response.assert_header("Content-Type", "text/plain; version=0.0.4; charset=utf-8");
+
+        let text = response.text();
+        assert!(text.contains(
+            r#"http_requests_total{method="GET",route="/api/categories",status="200"} 1"#
+        ));
+        assert!(text.contains(
+            r#"http_requests_total{method="GET",route="/api/categories/{id}",status="404"} 2"#
+        ));
+        assert!(
+            text.contains(r#"http_requests_total{method="GET",route="unmatched",status="404"} 1"#)
+        );
+        assert!(
+            text.contains(
+                r#"http_requests_total{method="OTHER",route="unmatched",status="404"} 1"#
+            )
+        );
+        assert!(!text.contains("PURGE"));
+        assert!(text.contains(
+            r#"http_request_duration_seconds_count{method="GET",route="/api/categories/{id}",status="404"} 2"#
+        ));
+        assert!(text.contains("http_requests_in_flight 1"));
+        assert!(text.contains("sqlx_pool_max_connections 7"));
+        assert!(text.contains("sqlx_pool_size 0"));
+        assert!(text.contains("sqlx_pool_acquire_wait_seconds_count 0"));
+        assert!(text.contains("tokio_workers 1"));
+        assert!(text.contains("tokio_alive_tasks"));
+    }
+}
@@ -0,0 +1,148 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::pagination::{Field, FieldKind, FieldValue, FilterOp, Listable};
+
+const ID_FILTERS: &[FilterOp] = &[
+    FilterOp::Eq,
+    FilterOp::Ne,
+    FilterOp::Gt,
+    FilterOp::Gte,
+    FilterOp::Lt,
+    FilterOp::Lte,
+];
+const NAME_FILTERS: &[FilterOp] = &[
+    FilterOp::Eq,
+    FilterOp::Contains,
+    FilterOp::IContains,
+    FilterOp::StartsWith,
+];
+const TEXT_FILTERS: &[FilterOp] = &[FilterOp::Contains, FilterOp::IContains];
+const TIME_FILTERS: &[FilterOp] = &[FilterOp::Gt, FilterOp::Gte, FilterOp::Lt, FilterOp::Lte];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Category {
+    pub id: i32,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Brand {
+    pub id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl Listable for Category {
+    const FIELDS: &'static [Field] = &[
+        Field {
+            name: "id",
+            kind: FieldKind::Int,
+            sortable: true,
+            filters: ID_FILTERS,
+        },
+        Field {
+            name: "name",
+            kind: FieldKind::Text,
+            sortable: true,
+            filters: NAME_FILTERS,
+        },
+        Field {
+            name: "description",
+            kind: FieldKind::Text,
+            sortable: false,
+            filters: TEXT_FILTERS,
+        },
+    ];
+
+    fn id(&self) -> i64 {
+        self.id as i64
+    }
+
+    fn value(&self, field: &str) -> FieldValue {
+        match field {
+            "name" => FieldValue::Text(Some(self.name.clone())),
+            "description" => FieldValue::Text(self.description.clone()),
+            _ => FieldValue::Int(self.id as i64),
+        }
+    }
+}
+
+impl Listable for Brand {
+    const FIELDS: &'static [Field] = &[
+        Field {
+            name: "id",
+            kind: FieldKind::Int,
+            sortable: true,
+            filters: ID_FILTERS,
+        },
+        Field {
+            name: "name",
+            kind: FieldKind::Text,
+            sortable: true,
+            filters: NAME_FILTERS,
+        },
+        Field {
+            name: "description",
+            kind: FieldKind::Text,
+            sortable: false,
+            filters: TEXT_FILTERS,
+        },
+        Field {
+            name: "created_at",
+            kind: FieldKind::DateTime,
+            sortable: true,
+            filters: TIME_FILTERS,
+        },
+        Field {
+            name: "updated_at",
+            kind: FieldKind::DateTime,
+            sortable: true,
+            filters: TIME_FILTERS,
+        },
+    ];
+
+    fn id(&self) -> i64 {
+        self.id as i64
+    }
+
+    fn value(&self, field: &str) -> FieldValue {
+        match field {
+            "name" => FieldValue::Text(Some(self.name.clone())),
+            "description" => FieldValue::Text(self.description.clone()),
+            "created_at" => FieldValue::DateTime(self.created_at),
+            "updated_at" => FieldValue::DateTime(self.updated_at),
+            _ => FieldValue::Int(self.id as i64),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+pub struct CategoryRequest {
+    #[validate(
+        custom(function = "crate::validation::not_blank"),
+        length(max = 100, message = "name must be at most 100 characters")
+    )]
+    #[schema(max_length = 100, pattern = r"\S")]
+    pub name: String,
+
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+pub struct BrandRequest {
+    #[validate(
+        custom(function = "crate::validation::not_blank"),
+        length(max = 100, message = "name must be at most 100 characters")
+    )]
+    #[schema(max_length = 100, pattern = r"\S")]
+    pub name: String,
+
+    pub description: Option<String>,
+}
@@ -0,0 +1,493 @@
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, FromRequestParts, Request},
+    response::{IntoResponse, Response},
+};
+use http::{
+    HeaderMap, HeaderValue, StatusCode,
+    header::{ACCEPT, CONTENT_TYPE, VARY},
+    request::Parts,
+};
+use serde::{Serialize, de::DeserializeOwned};
+use validator::ValidateArgs;
+
+use crate::{
+    error::AppError,
+    extract::{Json, ValidationArgs, validate},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Toml,
+    Form,
+    MessagePack,
+}
+
+impl Format {
+    pub const ALL: [Format; 4] = [
+        Format::Json,
+        Format::Toml,
+        Format::Form,
+        Format::MessagePack,
+    ];
+
+    /// Formats that can carry nested values such as lists.
+    pub const STRUCTURED: [Format; 3] = [Format::Json, Format::Toml, Format::MessagePack];
+
+    pub fn media_type(&self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            Format::Toml => "application/toml",
+            Format::Form => "application/x-www-form-urlencoded",
+            Format::MessagePack => "application/msgpack",
+        }
+    }
+
+    pub fn from_media_type(essence: &str) -> Option<Self> {
+        match essence {
+            "application/json" => Some(Format::Json),
+            "application/toml" => Some(Format::Toml),
+            "application/x-www-form-urlencoded" => Some(Format::Form),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(Format::MessagePack)
+            }
+            essence if essence.starts_with("application/") && essence.ends_with("+json") => {
+                Some(Format::Json)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn from_content_type(headers: &HeaderMap) -> Result<Self, AppError> {
+        headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(essence)
+            .and_then(|essence| Self::from_media_type(&essence))
+            .ok_or_else(|| {
+                AppError::new(
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    format!("Content-Type must be one of {}", supported(&Self::ALL)),
+                )
+            })
+    }
+
+    pub fn from_accept(headers: &HeaderMap) -> Result<Self, AppError> {
+        Self::negotiate(headers, &Self::ALL)
+    }
+
+    pub fn negotiate(headers: &HeaderMap, formats: &[Format]) -> Result<Self, AppError> {
+        let accept = headers
+            .get(ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("")
+            .trim();
+        if accept.is_empty() {
+            return Ok(Format::Json);
+        }
+
+        let ranges: Vec<MediaRange> = accept.split(',').filter_map(MediaRange::parse).collect();
+        formats
+            .iter()
+            .copied()
+            .filter_map(|format| {
+                ranges
+                    .iter()
+                    .filter_map(|range| range.matches(format).map(|score| (range.quality, score)))
+                    .max_by_key(|(_, score)| *score)
+                    .map(|(quality, score)| (format, quality, score))
+            })
+            .filter(|(_, quality, _)| *quality > 0.0)
+            .fold(None::<(Format, f32, u8)>, |best, candidate| match best {
+                Some(best) if (best.1, best.2) >= (candidate.1, candidate.2) => Some(best),
+                _ => Some(candidate),
+            })
+            .map(|(format, _, _)| format)
+            .ok_or_else(|| {
+                AppError::new(
+                    StatusCode::NOT_ACCEPTABLE,
+                    format!("Accept must allow one of {}", supported(formats)),
+                )
+            })
+    }
+
+    pub fn serialize<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, AppError> {
+        let encoded = match self {
+            Format::Json => serde_json::to_vec(value).map_err(|err| err.to_string()),
+            Format::Toml => toml::to_string(value)
+                .map(String::into_bytes)
+                .map_err(|err| err.to_string()),
+            Format::Form => serde_urlencoded::to_string(value)
+                .map(String::into_bytes)
+                .map_err(|err| err.to_string()),
+            Format::MessagePack => rmp_serde::to_vec_named(value).map_err(|err| err.to_string()),
+        };
+
+        encoded.map_err(|err| {
+            AppError::internal(format!(
+                "Failed to encode response as {} : {}",
+                self.media_type(),
+                err
+            ))
+        })
+    }
+
+    pub fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, AppError> {
+        let decoded = match self {
+            Format::Json => return Json::from_bytes(bytes).map(|Json(value)| value),
+            Format::Toml => std::str::from_utf8(bytes)
+                .map_err(|err| err.to_string())
+                .and_then(|text| toml::from_str(text).map_err(|err| err.to_string())),
+            Format::Form => serde_urlencoded::from_bytes(bytes).map_err(|err| err.to_string()),
+            Format::MessagePack => rmp_serde::from_slice(bytes).map_err(|err| err.to_string()),
+        };
+
+        decoded.map_err(|err| {
+            AppError::new(
+                StatusCode::BAD_REQUEST,
+                format!("Malformed {} body: {}", self.media_type(), err),
+            )
+        })
+    }
+}
+
+fn supported(formats: &[Format]) -> String {
+    formats
+        .iter()
+        .map(Format::media_type)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn essence(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase()
+}
+
+struct MediaRange {
+    essence: String,
+    quality: f32,
+}
+
+impl MediaRange {
+    fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.split(';');
+        let essence = parts.next()?.trim().to_ascii_lowercase();
+        if essence.is_empty() {
+            return None;
+        }
+
+        let quality = parts
+            .filter_map(|param| param.split_once('='))
+            .filter(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+            .find_map(|(_, q)| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+
+        Some(Self { essence, quality })
+    }
+
+    fn matches(&self, format: Format) -> Option<u8> {
+        match self.essence.as_str() {
+            "*/*" => Some(1),
+            "application/*" => Some(2),
+            essence => (Format::from_media_type(essence) == Some(format)).then_some(3),
+        }
+    }
+}
+
+pub struct Accept(pub Format);
+
+impl<S: Send + Sync> FromRequestParts<S> for Accept {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Format::from_accept(&parts.headers).map(Accept)
+    }
+}
+
+/// Like [`Accept`], but only offers formats that can represent lists.
+pub struct AcceptStructured(pub Format);
+
+impl<S: Send + Sync> FromRequestParts<S> for AcceptStructured {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Format::negotiate(&parts.headers, &Format::STRUCTURED).map(AcceptStructured)
+    }
+}
+
+pub struct Negotiated<T>(pub Format, pub T);
+
+impl<T, S> FromRequest<S> for Negotiated<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let format = Format::from_content_type(request.headers())?;
+        let bytes = Bytes::from_request(request, state)
+            .await
+            .map_err(|rejection| AppError::new(rejection.status(), rejection.body_text()))?;
+
+        format
+            .deserialize(&bytes)
+            .map(|value| Negotiated(format, value))
+    }
+}
+
+pub struct ValidatedNegotiated<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidatedNegotiated<T>
+where
+    T: DeserializeOwned + for<'v> ValidateArgs<'v>,
+    for<'v> <T as ValidateArgs<'v>>::Args: ValidationArgs<'v, S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Negotiated(_, value) = Negotiated::<T>::from_request(request, state).await?;
+        validate(&value, state)?;
+
+        Ok(ValidatedNegotiated(value))
+    }
+}
+
+impl<T> IntoResponse for Negotiated<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        let Negotiated(format, value) = self;
+
+        match format.serialize(&value) {
+            Ok(body) => (
+                [
+                    (CONTENT_TYPE, HeaderValue::from_static(format.media_type())),
+                    (VARY, HeaderValue::from_static("accept")),
+                ],
+                body,
+            )
+                .into_response(),
+            Err(err) => err.into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use axum::{Router, routing::post};
+    use axum_test::TestServer;
+    use http::{HeaderMap, HeaderValue, StatusCode, header::ACCEPT};
+    use serde::{Deserialize, Serialize};
+
+    use super::{Accept, Format, Negotiated};
+    use crate::{
+        app::{AppState, router},
+        auth::TokenService,
+        model::Category,
+        pagination::Page,
+        repository::MemoryRepository,
+    };
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Person {
+        name: String,
+        age: u8,
+    }
+
+    fn server() -> TestServer {
+        async fn echo(
+            Accept(format): Accept,
+            Negotiated(_, person): Negotiated<Person>,
+        ) -> Negotiated<Person> {
+            Negotiated(format, person)
+        }
+
+        TestServer::new(Router::new().route("/echo", post(echo))).unwrap()
+    }
+
+    fn person() -> Person {
+        Person {
+            name: "Rizki".to_string(),
+            age: 30,
+        }
+    }
+
+    fn accept(value: &str) -> Result<Format, StatusCode> {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_str(value).unwrap());
+        Format::from_accept(&headers).map_err(|err| err.status)
+    }
+
+    #[test]
+    fn test_accept_header() {
+        assert_eq!(
+            Format::from_accept(&HeaderMap::new()).ok(),
+            Some(Format::Json)
+        );
+        assert_eq!(accept("*/*"), Ok(Format::Json));
+        assert_eq!(accept("application/toml, */*;q=0.1"), Ok(Format::Toml));
+        assert_eq!(accept("application/json;q=0, */*"), Ok(Format::Toml));
+        assert_eq!(
+            accept("application/json;q=0.5, application/vnd.msgpack"),
+            Ok(Format::MessagePack)
+        );
+        assert_eq!(accept("application/json;Q=0, */*"), Ok(Format::Toml));
+        assert_eq!(accept("application/toml; q = 0.2, */*"), Ok(Format::Json));
+        assert_eq!(accept("text/html"), Err(StatusCode::NOT_ACCEPTABLE));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            ACCEPT,
+            HeaderValue::from_static("application/x-www-form-urlencoded, application/json;q=0.5"),
+        );
+        assert_eq!(
+            Format::negotiate(&headers, &Format::STRUCTURED).ok(),
+            Some(Format::Json)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_negotiated_formats() {
+        let server = server();
+
+        let response = server
+            .post("/echo")
+            .json(&person())
+            .add_header("Accept", "application/toml")
+            .await;
+        response.assert_status_ok();
+        response.assert_header("Content-Type", "application/toml");
+        response.assert_header("Vary", "accept");
+        assert_eq!(
+            toml::from_str::<Person>(&response.text()).unwrap(),
+            person()
+        );
+
+        let response = server
+            .post("/echo")
+            .bytes("name=Rizki&age=30".into())
+            .content_type("application/x-www-form-urlencoded")
+            .add_header("Accept", "application/msgpack")
+            .await;
+        response.assert_status_ok();
+        response.assert_header("Content-Type", "application/msgpack");
+        assert_eq!(
+            rmp_serde::from_slice::<Person>(response.as_bytes()).unwrap(),
+            person()
+        );
+
+        let response = server
+            .post("/echo")
+            .bytes(rmp_serde::to_vec_named(&person()).unwrap().into())
+            .content_type("application/msgpack")
+            .add_header("Accept", "application/x-www-form-urlencoded")
+            .await;
+        response.assert_status_ok();
+        assert_eq!(response.text(), "name=Rizki&age=30");
+
+        let response = server
+            .post("/echo")
+            .bytes("name = \"Rizki\"\nage = 30\n".into())
+            .content_type("application/toml")
+            .await;
+        response.assert_status_ok();
+        response.assert_header("Content-Type", "application/json");
+        assert_eq!(response.json::<Person>(), person());
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_formats() {
+        let server = server();
+
+        server
+            .post("/echo")
+            .json(&person())
+            .add_header("Accept", "text/html")
+            .await
+            .assert_status(StatusCode::NOT_ACCEPTABLE);
+
+        server
+            .post("/echo")
+            .bytes("Rizki".into())
+            .content_type("text/plain")
+            .await
+            .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        server
+            .post("/echo")
+            .bytes("name = ".into())
+            .content_type("application/toml")
+            .await
+            .assert_status_bad_request();
+    }
+
+    #[tokio::test]
+    async fn test_negotiated_resources() {
+        let state = AppState::new(
+            Arc::new(MemoryRepository::new()),
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        );
+        let server = TestServer::new(router(state)).unwrap();
+
+        let response = server
+            .post("/api/categories")
+            .bytes("name = \"Gadget\"\n".into())
+            .content_type("application/toml")
+            .add_header("Accept", "application/msgpack")
+            .await;
+        response.assert_status(StatusCode::CREATED);
+        response.assert_header("Content-Type", "application/msgpack");
+        let category: Category = rmp_serde::from_slice(response.as_bytes()).unwrap();
+        assert_eq!(category.name, "Gadget");
+
+        let response = server
+            .get("/api/categories/1")
+            .add_header("Accept", "application/toml")
+            .await;
+        response.assert_header("Content-Type", "application/toml");
+        assert!(response.maybe_header("ETag").is_some());
+        assert_eq!(
+            toml::from_str::<Category>(&response.text()).unwrap(),
+            category
+        );
+
+        let response = server
+            .get("/api/categories")
+            .add_header(
+                "Accept",
+                "application/x-www-form-urlencoded, application/json;Q=0.5",
+            )
+            .await;
+        response.assert_header("Content-Type", "application/json");
+        assert_eq!(response.json::<Page<Category>>().total, 1);
+        server
+            .get("/api/categories")
+            .add_header("Accept", "application/x-www-form-urlencoded")
+            .await
+            .assert_status(StatusCode::NOT_ACCEPTABLE);
+
+        server
+            .post("/api/brands")
+            .json(&serde_json::json!({ "name": "" }))
+            .add_header("Accept", "text/html")
+            .await
+            .assert_status(StatusCode::NOT_ACCEPTABLE);
+        server
+            .put("/api/categories/1")
+            .json(&serde_json::json!({ "name": "" }))
+            .add_header("Accept", "text/html")
+            .await
+            .assert_status(StatusCode::NOT_ACCEPTABLE);
+    }
+}
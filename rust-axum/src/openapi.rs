@@ -0,0 +1,164 @@
+use axum::{Json, Router, routing::get};
+use utoipa::{
+    Modify, OpenApi,
+    openapi::{
+        self,
+        security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+    },
+};
+
+use crate::{app::AppState, error::Problem};
+
+const BEARER: &str = "bearer";
+const SESSION: &str = "session";
+
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            BEARER,
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            SESSION,
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new("session_id"))),
+        );
+    }
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Rust Axum"),
+    components(schemas(Problem)),
+    modifiers(&SecuritySchemes),
+    tags(
+        (name = "categories"),
+        (name = "brands"),
+        (name = "users"),
+        (name = "sessions"),
+        (name = "uploads"),
+        (name = "live"),
+        (name = "jobs"),
+        (name = "health"),
+        (name = "metrics")
+    )
+)]
+pub struct ApiDoc;
+
+pub fn routes(openapi: openapi::OpenApi) -> Router<AppState> {
+    let router = Router::new().route(
+        "/openapi.json",
+        get(move || {
+            let openapi = openapi.clone();
+            async move { Json(openapi) }
+        }),
+    );
+
+    #[cfg(feature = "swagger-ui")]
+    let router = router.merge(
+        utoipa_swagger_ui::SwaggerUi::new("/swagger-ui")
+            .config(utoipa_swagger_ui::Config::from("/openapi.json")),
+    );
+
+    router
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use axum_test::TestServer;
+    use serde_json::Value;
+
+    use crate::{
+        app::{AppState, router},
+        auth::TokenService,
+        repository::MemoryRepository,
+    };
+
+    fn server() -> TestServer {
+        let state = AppState::new(
+            Arc::new(MemoryRepository::new()),
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        );
+        TestServer::new(router(state)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_openapi_document() {
+        let server = server();
+
+        let response = server.get("/openapi.json").await;
+        response.assert_status_ok();
+        let document: Value = response.json();
+        assert_eq!(document["openapi"], "3.1.0");
+
+        let paths = &document["paths"];
+        for path in [
+            "/api/categories",
+            "/api/categories/{id}",
+            "/api/brands",
+            "/api/brands/{id}",
+            "/api/users/login",
+            "/api/users/current",
+            "/api/sessions",
+            "/api/sessions/current",
+            "/api/uploads/profile-picture",
+            "/api/live",
+            "/api/brands/import",
+            "/api/jobs/{id}/events",
+        ] {
+            assert!(paths[path].is_object(), "missing path {}", path);
+        }
+        assert!(paths["/api/categories"]["post"].is_object());
+        assert!(paths["/api/categories/{id}"]["delete"].is_object());
+        assert_eq!(
+            paths["/api/users/current"]["get"]["security"][0]["bearer"],
+            serde_json::json!([])
+        );
+
+        let parameters: Vec<&str> = paths["/api/categories"]["get"]["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|parameter| parameter["name"].as_str().unwrap())
+            .collect();
+        assert!(parameters.contains(&"per_page"));
+        assert!(parameters.contains(&"name__contains"));
+        assert!(!parameters.contains(&"created_at__gt"));
+
+        let schemas = &document["components"]["schemas"];
+        let username = &schemas["LoginRequest"]["properties"]["username"];
+        assert_eq!(username["minLength"], 3);
+        assert_eq!(username["maxLength"], 20);
+        assert_eq!(schemas["LoginResponse"]["required"][0], "token");
+        assert_eq!(
+            schemas["CategoryRequest"]["properties"]["name"]["maxLength"],
+            100
+        );
+        assert!(schemas["Category"]["properties"]["description"].is_object());
+        assert!(schemas["Problem"].is_object());
+        assert!(document["components"]["securitySchemes"]["session"].is_object());
+    }
+
+    #[cfg(feature = "swagger-ui")]
+    #[tokio::test]
+    async fn test_swagger_ui() {
+        let server = server();
+
+        let response = server.get("/swagger-ui/").await;
+        response.assert_status_ok();
+        assert!(response.text().contains("swagger-ui"));
+
+        let response = server.get("/swagger-ui/swagger-initializer.js").await;
+        response.assert_status_ok();
+        assert!(response.text().contains("/openapi.json"));
+    }
+}
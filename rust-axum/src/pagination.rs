@@ -0,0 +1,747 @@
+use std::{cmp::Ordering, marker::PhantomData};
+
+use axum::extract::{FromRequestParts, OriginalUri, Query};
+use chrono::NaiveDateTime;
+use http::{StatusCode, request::Parts};
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
+use utoipa::{
+    IntoParams, ToSchema,
+    openapi::{
+        Required,
+        path::{Parameter, ParameterBuilder, ParameterIn},
+        schema::{KnownFormat, ObjectBuilder, SchemaFormat, Type},
+    },
+};
+
+use crate::error::AppError;
+
+pub const DEFAULT_PER_PAGE: u32 = 20;
+pub const MAX_PER_PAGE: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldKind {
+    Int,
+    Text,
+    DateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Contains,
+    IContains,
+    StartsWith,
+}
+
+impl FilterOp {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "eq" => Some(Self::Eq),
+            "ne" => Some(Self::Ne),
+            "gt" => Some(Self::Gt),
+            "gte" => Some(Self::Gte),
+            "lt" => Some(Self::Lt),
+            "lte" => Some(Self::Lte),
+            "contains" => Some(Self::Contains),
+            "icontains" => Some(Self::IContains),
+            "startswith" => Some(Self::StartsWith),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Eq => "eq",
+            Self::Ne => "ne",
+            Self::Gt => "gt",
+            Self::Gte => "gte",
+            Self::Lt => "lt",
+            Self::Lte => "lte",
+            Self::Contains => "contains",
+            Self::IContains => "icontains",
+            Self::StartsWith => "startswith",
+        }
+    }
+
+    fn sql(&self) -> &'static str {
+        match self {
+            Self::Eq => " = ",
+            Self::Ne => " <> ",
+            Self::Gt => " > ",
+            Self::Gte => " >= ",
+            Self::Lt => " < ",
+            Self::Lte => " <= ",
+            Self::Contains | Self::StartsWith => " LIKE ",
+            Self::IContains => " ILIKE ",
+        }
+    }
+}
+
+pub struct Field {
+    pub name: &'static str,
+    pub kind: FieldKind,
+    pub sortable: bool,
+    pub filters: &'static [FilterOp],
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum FieldValue {
+    Int(i64),
+    Text(Option<String>),
+    DateTime(NaiveDateTime),
+}
+
+pub trait Listable {
+    const FIELDS: &'static [Field];
+
+    fn id(&self) -> i64;
+    fn value(&self, field: &str) -> FieldValue;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sort {
+    pub field: &'static str,
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    pub field: &'static str,
+    pub op: FilterOp,
+    pub value: FieldValue,
+}
+
+impl Filter {
+    fn matches(&self, value: &FieldValue) -> bool {
+        match (self.op, value, &self.value) {
+            (FilterOp::Eq, value, expected) => value == expected,
+            (FilterOp::Ne, value, expected) => value != expected,
+            (FilterOp::Gt, value, expected) => value > expected,
+            (FilterOp::Gte, value, expected) => value >= expected,
+            (FilterOp::Lt, value, expected) => value < expected,
+            (FilterOp::Lte, value, expected) => value <= expected,
+            (
+                FilterOp::Contains,
+                FieldValue::Text(Some(value)),
+                FieldValue::Text(Some(expected)),
+            ) => value.contains(expected.as_str()),
+            (
+                FilterOp::IContains,
+                FieldValue::Text(Some(value)),
+                FieldValue::Text(Some(expected)),
+            ) => value.to_lowercase().contains(&expected.to_lowercase()),
+            (
+                FilterOp::StartsWith,
+                FieldValue::Text(Some(value)),
+                FieldValue::Text(Some(expected)),
+            ) => value.starts_with(expected.as_str()),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+    pub per_page: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ListQuery<T> {
+    pub page: u32,
+    pub per_page: u32,
+    pub after: Option<i64>,
+    pub sort: Vec<Sort>,
+    pub filters: Vec<Filter>,
+    path: String,
+    params: Vec<(String, String)>,
+    resource: PhantomData<fn() -> T>,
+}
+
+fn bad_request(parameter: &str, detail: String) -> AppError {
+    AppError::new(StatusCode::BAD_REQUEST, detail).with_extension("parameter", parameter)
+}
+
+fn parse_number<N>(key: &str, value: &str) -> Result<N, AppError>
+where
+    N: std::str::FromStr + PartialOrd + Default,
+{
+    value
+        .parse()
+        .ok()
+        .filter(|number| *number >= N::default())
+        .ok_or_else(|| {
+            bad_request(
+                key,
+                format!("Query parameter {} must be a positive number", key),
+            )
+        })
+}
+
+fn parse_value(key: &str, kind: FieldKind, value: &str) -> Result<FieldValue, AppError> {
+    match kind {
+        FieldKind::Int => value
+            .parse()
+            .map(FieldValue::Int)
+            .map_err(|_| bad_request(key, format!("Query parameter {} must be an integer", key))),
+        FieldKind::Text => Ok(FieldValue::Text(Some(value.to_string()))),
+        FieldKind::DateTime => value.parse().map(FieldValue::DateTime).map_err(|_| {
+            bad_request(
+                key,
+                format!(
+                    "Query parameter {} must be a date time like 2025-03-14T10:00:00",
+                    key
+                ),
+            )
+        }),
+    }
+}
+
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+impl<T: Listable> ListQuery<T> {
+    pub fn parse(path: &str, params: Vec<(String, String)>) -> Result<Self, AppError> {
+        let mut query = ListQuery {
+            page: 1,
+            per_page: DEFAULT_PER_PAGE,
+            after: None,
+            sort: Vec::new(),
+            filters: Vec::new(),
+            path: path.to_string(),
+            params: Vec::new(),
+            resource: PhantomData,
+        };
+        let mut has_page = false;
+
+        for (key, value) in &params {
+            match key.as_str() {
+                "page" => {
+                    query.page = parse_number(key, value)?;
+                    has_page = true;
+                    if query.page == 0 {
+                        return Err(bad_request(
+                            key,
+                            "Query parameter page must be at least 1".to_string(),
+                        ));
+                    }
+                }
+                "per_page" => {
+                    query.per_page = parse_number(key, value)?;
+                    if query.per_page == 0 || query.per_page > MAX_PER_PAGE {
+                        return Err(bad_request(
+                            key,
+                            format!(
+                                "Query parameter per_page must be between 1 and {}",
+                                MAX_PER_PAGE
+                            ),
+                        ));
+                    }
+                }
+                "after" => query.after = Some(parse_number(key, value)?),
+                "sort" => query.sort = Self::parse_sort(value)?,
+                _ => query.filters.push(Self::parse_filter(key, value)?),
+            }
+        }
+
+        if query.after.is_some() {
+            if has_page {
+                return Err(bad_request(
+                    "after",
+                    "Query parameters page and after cannot be combined".to_string(),
+                ));
+            }
+            if query.sort.iter().any(|sort| sort.field != "id") {
+                return Err(bad_request(
+                    "after",
+                    "Query parameter after can only be combined with sort=id or sort=-id"
+                        .to_string(),
+                ));
+            }
+        }
+
+        query.params = params
+            .into_iter()
+            .filter(|(key, _)| key != "page" && key != "after")
+            .collect();
+
+        Ok(query)
+    }
+
+    fn parse_sort(value: &str) -> Result<Vec<Sort>, AppError> {
+        value
+            .split(',')
+            .map(|item| {
+                let (name, descending) = match item.strip_prefix('-') {
+                    Some(name) => (name, true),
+                    None => (item, false),
+                };
+
+                T::FIELDS
+                    .iter()
+                    .find(|field| field.sortable && field.name == name)
+                    .map(|field| Sort {
+                        field: field.name,
+                        descending,
+                    })
+                    .ok_or_else(|| bad_request("sort", format!("Cannot sort by field {:?}", name)))
+            })
+            .collect()
+    }
+
+    fn parse_filter(key: &str, value: &str) -> Result<Filter, AppError> {
+        let (name, op) = match key.split_once("__") {
+            Some((name, op)) => (name, FilterOp::parse(op)),
+            None => (key, Some(FilterOp::Eq)),
+        };
+
+        let field = T::FIELDS.iter().find(|field| field.name == name);
+        match (field, op) {
+            (Some(field), Some(op)) if field.filters.contains(&op) => Ok(Filter {
+                field: field.name,
+                op,
+                value: parse_value(key, field.kind, value)?,
+            }),
+            _ => Err(bad_request(key, format!("Unknown query parameter {}", key))),
+        }
+    }
+
+    fn id_descending(&self) -> bool {
+        self.sort
+            .first()
+            .is_some_and(|sort| sort.field == "id" && sort.descending)
+    }
+
+    fn ordering(&self) -> Vec<Sort> {
+        let mut ordering = self.sort.clone();
+        if !ordering.iter().any(|sort| sort.field == "id") {
+            ordering.push(Sort {
+                field: "id",
+                descending: false,
+            });
+        }
+
+        ordering
+    }
+
+    pub fn offset(&self) -> i64 {
+        if self.after.is_some() {
+            0
+        } else {
+            (self.page as i64 - 1) * self.per_page as i64
+        }
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.per_page as i64 + 1
+    }
+
+    pub fn apply(&self, items: Vec<T>) -> (Vec<T>, i64) {
+        let mut items: Vec<T> = items
+            .into_iter()
+            .filter(|item| {
+                self.filters
+                    .iter()
+                    .all(|filter| filter.matches(&item.value(filter.field)))
+            })
+            .collect();
+        let total = items.len() as i64;
+
+        let ordering = self.ordering();
+        items.sort_by(|left, right| {
+            ordering
+                .iter()
+                .map(|sort| {
+                    let order = left
+                        .value(sort.field)
+                        .partial_cmp(&right.value(sort.field))
+                        .unwrap_or(Ordering::Equal);
+                    if sort.descending {
+                        order.reverse()
+                    } else {
+                        order
+                    }
+                })
+                .find(|order| *order != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        });
+
+        if let Some(after) = self.after {
+            let descending = self.id_descending();
+            items.retain(|item| {
+                if descending {
+                    item.id() < after
+                } else {
+                    item.id() > after
+                }
+            });
+        }
+
+        let items = items
+            .into_iter()
+            .skip(self.offset() as usize)
+            .take(self.limit() as usize)
+            .collect();
+
+        (items, total)
+    }
+
+    fn push_where(&self, builder: &mut QueryBuilder<'static, Postgres>, cursor: bool) {
+        builder.push(" WHERE 1 = 1");
+
+        for filter in &self.filters {
+            builder
+                .push(" AND ")
+                .push(filter.field)
+                .push(filter.op.sql());
+            match (&filter.value, filter.op) {
+                (FieldValue::Text(Some(value)), FilterOp::Contains | FilterOp::IContains) => {
+                    builder.push_bind(format!("%{}%", escape_like(value)));
+                }
+                (FieldValue::Text(Some(value)), FilterOp::StartsWith) => {
+                    builder.push_bind(format!("{}%", escape_like(value)));
+                }
+                (FieldValue::Int(value), _) => {
+                    builder.push_bind(*value);
+                }
+                (FieldValue::Text(value), _) => {
+                    builder.push_bind(value.clone());
+                }
+                (FieldValue::DateTime(value), _) => {
+                    builder.push_bind(*value);
+                }
+            }
+        }
+
+        if let (true, Some(after)) = (cursor, self.after) {
+            let op = if self.id_descending() { " < " } else { " > " };
+            builder.push(" AND id").push(op).push_bind(after);
+        }
+    }
+
+    pub fn select_sql(&self, table: &str) -> QueryBuilder<'static, Postgres> {
+        let mut builder = QueryBuilder::new(format!("SELECT * FROM {}", table));
+        self.push_where(&mut builder, true);
+
+        builder.push(" ORDER BY ");
+        let mut separated = builder.separated(", ");
+        for sort in self.ordering() {
+            separated.push(format!(
+                "{} {}",
+                sort.field,
+                if sort.descending { "DESC" } else { "ASC" }
+            ));
+        }
+
+        builder
+            .push(" LIMIT ")
+            .push_bind(self.limit())
+            .push(" OFFSET ")
+            .push_bind(self.offset());
+
+        builder
+    }
+
+    pub fn count_sql(&self, table: &str) -> QueryBuilder<'static, Postgres> {
+        let mut builder = QueryBuilder::new(format!("SELECT COUNT(*) FROM {}", table));
+        self.push_where(&mut builder, false);
+
+        builder
+    }
+
+    fn link(&self, param: (&str, String)) -> String {
+        let mut params: Vec<(&str, &str)> = self
+            .params
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        params.push((param.0, param.1.as_str()));
+
+        format!(
+            "{}?{}",
+            self.path,
+            serde_urlencoded::to_string(params).unwrap_or_default()
+        )
+    }
+
+    pub fn into_page(self, mut items: Vec<T>, total: i64) -> Page<T> {
+        let has_more = items.len() > self.per_page as usize;
+        items.truncate(self.per_page as usize);
+
+        if self.after.is_some() {
+            let next = match items.last() {
+                Some(last) if has_more => Some(self.link(("after", last.id().to_string()))),
+                _ => None,
+            };
+
+            return Page {
+                items,
+                total,
+                per_page: self.per_page,
+                page: None,
+                next,
+                prev: None,
+            };
+        }
+
+        let next = has_more.then(|| self.link(("page", (self.page + 1).to_string())));
+        let prev = (self.page > 1).then(|| self.link(("page", (self.page - 1).to_string())));
+
+        Page {
+            items,
+            total,
+            per_page: self.per_page,
+            page: Some(self.page),
+            next,
+            prev,
+        }
+    }
+}
+
+fn query_parameter(name: String, schema: ObjectBuilder, description: String) -> Parameter {
+    ParameterBuilder::new()
+        .name(name)
+        .parameter_in(ParameterIn::Query)
+        .required(Required::False)
+        .description(Some(description))
+        .schema(Some(schema))
+        .build()
+}
+
+impl<T: Listable> IntoParams for ListQuery<T> {
+    fn into_params(_parameter_in_provider: impl Fn() -> Option<ParameterIn>) -> Vec<Parameter> {
+        let sortable: Vec<&str> = T::FIELDS
+            .iter()
+            .filter(|field| field.sortable)
+            .map(|field| field.name)
+            .collect();
+
+        let mut parameters = vec![
+            query_parameter(
+                "page".to_string(),
+                ObjectBuilder::new()
+                    .schema_type(Type::Integer)
+                    .minimum(Some(1)),
+                "Page number, starting at 1".to_string(),
+            ),
+            query_parameter(
+                "per_page".to_string(),
+                ObjectBuilder::new()
+                    .schema_type(Type::Integer)
+                    .minimum(Some(1))
+                    .maximum(Some(MAX_PER_PAGE))
+                    .default(Some(DEFAULT_PER_PAGE.into())),
+                "Number of items per page".to_string(),
+            ),
+            query_parameter(
+                "after".to_string(),
+                ObjectBuilder::new().schema_type(Type::Integer),
+                "Return items after this id, cannot be combined with page".to_string(),
+            ),
+            query_parameter(
+                "sort".to_string(),
+                ObjectBuilder::new().schema_type(Type::String),
+                format!(
+                    "Comma separated fields, prefix with - to sort descending: {}",
+                    sortable.join(", ")
+                ),
+            ),
+        ];
+
+        for field in T::FIELDS {
+            let (schema_type, format) = match field.kind {
+                FieldKind::Int => (Type::Integer, None),
+                FieldKind::Text => (Type::String, None),
+                FieldKind::DateTime => (
+                    Type::String,
+                    Some(SchemaFormat::KnownFormat(KnownFormat::DateTime)),
+                ),
+            };
+
+            for op in field.filters {
+                let name = match op {
+                    FilterOp::Eq => field.name.to_string(),
+                    op => format!("{}__{}", field.name, op.name()),
+                };
+                parameters.push(query_parameter(
+                    name,
+                    ObjectBuilder::new()
+                        .schema_type(schema_type.clone())
+                        .format(format.clone()),
+                    format!("Filter {} with {}", field.name, op.name()),
+                ));
+            }
+        }
+
+        parameters
+    }
+}
+
+impl<T: Listable, S: Send + Sync> FromRequestParts<S> for ListQuery<T> {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Query(params) = Query::<Vec<(String, String)>>::try_from_uri(&parts.uri)
+            .map_err(|rejection| AppError::new(rejection.status(), rejection.body_text()))?;
+        let path = parts
+            .extensions
+            .get::<OriginalUri>()
+            .map(|uri| uri.path().to_string())
+            .unwrap_or_else(|| parts.uri.path().to_string());
+
+        Self::parse(&path, params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FieldValue, FilterOp, ListQuery, Sort};
+    use crate::model::Category;
+
+    fn parse(query: &str) -> Result<ListQuery<Category>, String> {
+        let params = serde_urlencoded::from_str(query).unwrap();
+        ListQuery::parse("/api/categories", params).map_err(|err| err.detail)
+    }
+
+    fn categories() -> Vec<Category> {
+        ["Gadget", "fashion", "Food", "Book"]
+            .iter()
+            .enumerate()
+            .map(|(index, name)| Category {
+                id: index as i32 + 1,
+                name: name.to_string(),
+                description: (index % 2 == 0).then(|| format!("{} description", name)),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let query = parse("page=2&per_page=5&sort=name,-id&name__icontains=fa&id__gt=1").unwrap();
+        assert_eq!(query.page, 2);
+        assert_eq!(query.per_page, 5);
+        assert_eq!(
+            query.sort,
+            vec![
+                Sort {
+                    field: "name",
+                    descending: false
+                },
+                Sort {
+                    field: "id",
+                    descending: true
+                }
+            ]
+        );
+        assert_eq!(query.filters[0].op, FilterOp::IContains);
+        assert_eq!(query.filters[1].value, FieldValue::Int(1));
+
+        let query = parse("").unwrap();
+        assert_eq!((query.page, query.per_page), (1, 20));
+    }
+
+    #[test]
+    fn test_reject_invalid_query() {
+        assert_eq!(
+            parse("page=0").unwrap_err(),
+            "Query parameter page must be at least 1"
+        );
+        assert_eq!(
+            parse("per_page=500").unwrap_err(),
+            "Query parameter per_page must be between 1 and 100"
+        );
+        assert_eq!(
+            parse("page=abc").unwrap_err(),
+            "Query parameter page must be a positive number"
+        );
+        assert_eq!(
+            parse("sort=password").unwrap_err(),
+            "Cannot sort by field \"password\""
+        );
+        assert_eq!(
+            parse("name__gt=a").unwrap_err(),
+            "Unknown query parameter name__gt"
+        );
+        assert_eq!(
+            parse("password=rahasia").unwrap_err(),
+            "Unknown query parameter password"
+        );
+        assert_eq!(
+            parse("id__gt=satu").unwrap_err(),
+            "Query parameter id__gt must be an integer"
+        );
+        assert_eq!(
+            parse("after=-1").unwrap_err(),
+            "Query parameter after must be a positive number"
+        );
+        assert_eq!(
+            parse("after=1&page=2").unwrap_err(),
+            "Query parameters page and after cannot be combined"
+        );
+        assert_eq!(
+            parse("after=1&sort=name").unwrap_err(),
+            "Query parameter after can only be combined with sort=id or sort=-id"
+        );
+    }
+
+    #[test]
+    fn test_apply_query() {
+        let (items, total) = parse("name__icontains=F").unwrap().apply(categories());
+        assert_eq!(total, 2);
+        assert_eq!(items.iter().map(|c| c.id).collect::<Vec<_>>(), vec![2, 3]);
+
+        let (items, total) = parse("name__contains=F").unwrap().apply(categories());
+        assert_eq!(total, 1);
+        assert_eq!(items[0].name, "Food");
+
+        let (items, _) = parse("description__contains=description&sort=-name")
+            .unwrap()
+            .apply(categories());
+        assert_eq!(items.iter().map(|c| c.id).collect::<Vec<_>>(), vec![1, 3]);
+
+        let (items, total) = parse("sort=-id&after=3&per_page=1")
+            .unwrap()
+            .apply(categories());
+        assert_eq!(total, 4);
+        assert_eq!(items.iter().map(|c| c.id).collect::<Vec<_>>(), vec![2, 1]);
+
+        let query = parse("sort=-id&after=3&per_page=1").unwrap();
+        let (items, total) = query.apply(categories());
+        let page = query.into_page(items, total);
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(
+            page.next.as_deref(),
+            Some("/api/categories?sort=-id&per_page=1&after=2")
+        );
+    }
+
+    #[test]
+    fn test_build_sql() {
+        let query = parse("name__icontains=50%&id__gte=2&sort=-name&page=3&per_page=10").unwrap();
+
+        assert_eq!(
+            query.select_sql("categories").sql(),
+            "SELECT * FROM categories WHERE 1 = 1 AND name ILIKE $1 AND id >= $2 \
+             ORDER BY name DESC, id ASC LIMIT $3 OFFSET $4"
+        );
+        assert_eq!(
+            query.count_sql("categories").sql(),
+            "SELECT COUNT(*) FROM categories WHERE 1 = 1 AND name ILIKE $1 AND id >= $2"
+        );
+    }
+}
@@ -0,0 +1,520 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header::RETRY_AFTER};
+use serde::Deserialize;
+
+use crate::{app::AppState, auth::bearer_token, error::AppError, timeout::matches_prefix};
+
+pub const X_API_KEY: HeaderName = HeaderName::from_static("x-api-key");
+pub const RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+pub const RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+pub const RATELIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+pub const RATELIMIT_POLICY: HeaderName = HeaderName::from_static("ratelimit-policy");
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitRule {
+    capacity: u32,
+    period_secs: u64,
+}
+
+impl RateLimitRule {
+    pub fn new(capacity: u32, period_secs: u64) -> Result<Self, String> {
+        if capacity == 0 {
+            return Err("capacity must be greater than 0".to_string());
+        }
+        if period_secs == 0 {
+            return Err("period_secs must be greater than 0".to_string());
+        }
+
+        Ok(Self {
+            capacity,
+            period_secs,
+        })
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    fn period(&self) -> Duration {
+        Duration::from_secs(self.period_secs)
+    }
+
+    fn refill_per_sec(&self) -> f64 {
+        self.capacity as f64 / self.period().as_secs_f64()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RouteLimit {
+    pub name: String,
+    pub prefix: String,
+    pub capacity: u32,
+    pub period_secs: u64,
+}
+
+impl RouteLimit {
+    pub fn rule(&self) -> Result<RateLimitRule, String> {
+        RateLimitRule::new(self.capacity, self.period_secs)
+            .map_err(|err| format!("route {}: {}", self.name, err))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    pub reset: Duration,
+    pub retry_after: Option<Duration>,
+}
+
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    async fn take(&self, key: &str, rule: &RateLimitRule) -> anyhow::Result<Decision>;
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    idle_until: Instant,
+}
+
+struct Buckets {
+    entries: HashMap<String, Bucket>,
+    swept: Instant,
+}
+
+/// Buckets that have been idle for a full period are back at capacity, so
+/// they are evicted by a sweep that runs at most once per sweep interval.
+pub struct MemoryRateLimitStore {
+    buckets: Mutex<Buckets>,
+    sweep_interval: Duration,
+}
+
+impl Default for MemoryRateLimitStore {
+    fn default() -> Self {
+        Self {
+            buckets: Mutex::new(Buckets {
+                entries: HashMap::new(),
+                swept: Instant::now(),
+            }),
+            sweep_interval: SWEEP_INTERVAL,
+        }
+    }
+}
+
+impl MemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_sweep_interval(mut self, sweep_interval: Duration) -> Self {
+        self.sweep_interval = sweep_interval;
+        self
+    }
+
+    pub fn buckets(&self) -> usize {
+        self.buckets.lock().unwrap().entries.len()
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn take(&self, key: &str, rule: &RateLimitRule) -> anyhow::Result<Decision> {
+        let now = Instant::now();
+        let capacity = rule.capacity as f64;
+        let refill = rule.refill_per_sec();
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if now.duration_since(buckets.swept) >= self.sweep_interval {
+            buckets.entries.retain(|_, bucket| bucket.idle_until > now);
+            buckets.swept = now;
+        }
+
+        let bucket = buckets.entries.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+            idle_until: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill).min(capacity);
+        bucket.updated = now;
+        bucket.idle_until = now + rule.period();
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+
+        Ok(Decision {
+            allowed,
+            limit: rule.capacity,
+            remaining: bucket.tokens.floor() as u32,
+            reset: Duration::from_secs_f64((capacity - bucket.tokens) / refill),
+            retry_after: (!allowed)
+                .then(|| Duration::from_secs_f64((1.0 - bucket.tokens) / refill)),
+        })
+    }
+}
+
+struct Route {
+    name: String,
+    prefix: String,
+    rule: RateLimitRule,
+}
+
+pub struct RateLimiter {
+    store: Arc<dyn RateLimitStore>,
+    default: Option<RateLimitRule>,
+    routes: Vec<Route>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(Arc::new(MemoryRateLimitStore::new()))
+    }
+}
+
+impl RateLimiter {
+    pub fn new(store: Arc<dyn RateLimitStore>) -> Self {
+        Self {
+            store,
+            default: None,
+            routes: Vec::new(),
+        }
+    }
+
+    pub fn with_default(mut self, rule: RateLimitRule) -> Self {
+        self.default = Some(rule);
+        self
+    }
+
+    pub fn with_route(mut self, route: RouteLimit) -> Self {
+        let rule = match route.rule() {
+            Ok(rule) => rule,
+            Err(err) => {
+                eprintln!("Rate limit is disabled for {} : {}", route.prefix, err);
+                return self;
+            }
+        };
+        self.routes.push(Route {
+            name: route.name,
+            prefix: route.prefix,
+            rule,
+        });
+        self.routes
+            .sort_by_key(|route| std::cmp::Reverse(route.prefix.len()));
+        self
+    }
+
+    pub fn rule_for(&self, path: &str) -> Option<(&str, RateLimitRule)> {
+        self.routes
+            .iter()
+            .find(|route| matches_prefix(path, &route.prefix))
+            .map(|route| (route.name.as_str(), route.rule))
+            .or_else(|| self.default.map(|rule| ("default", rule)))
+    }
+}
+
+fn client_key(state: &AppState, headers: &HeaderMap, client: Option<SocketAddr>) -> String {
+    let key = headers
+        .get(X_API_KEY)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|key| state.api_keys.verify(key));
+    if let Some(name) = key {
+        return format!("key:{}", name);
+    }
+
+    let user = bearer_token(headers).and_then(|token| state.tokens.verify(token).ok());
+    if let Some(claims) = user {
+        return format!("user:{}", claims.sub);
+    }
+
+    match client {
+        Some(client) => format!("ip:{}", client.ip()),
+        None => "ip:unknown".to_string(),
+    }
+}
+
+fn insert_headers(headers: &mut HeaderMap, decision: &Decision, rule: &RateLimitRule) {
+    let mut insert = |name: HeaderName, value: String| {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(name, value);
+        }
+    };
+
+    insert(RATELIMIT_LIMIT, decision.limit.to_string());
+    insert(RATELIMIT_REMAINING, decision.remaining.to_string());
+    insert(
+        RATELIMIT_RESET,
+        decision.reset.as_secs_f64().ceil().to_string(),
+    );
+    insert(
+        RATELIMIT_POLICY,
+        format!("{};w={}", rule.capacity, rule.period().as_secs()),
+    );
+    if let Some(retry_after) = decision.retry_after {
+        insert(
+            RETRY_AFTER,
+            retry_after.as_secs_f64().ceil().max(1.0).to_string(),
+        );
+    }
+}
+
+pub async fn rate_limit_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some((group, rule)) = state.rate_limiter.rule_for(request.uri().path()) else {
+        return next.run(request).await;
+    };
+
+    let client = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(address)| *address);
+    let key = format!(
+        "{}:{}",
+        group,
+        client_key(&state, request.headers(), client)
+    );
+
+    let decision = match state.rate_limiter.store.take(&key, &rule).await {
+        Ok(decision) => decision,
+        Err(err) => {
+            eprintln!("Rate limit store error : {:#}", err);
+            return next.run(request).await;
+        }
+    };
+
+    let mut response = if decision.allowed {
+        next.run(request).await
+    } else {
+        AppError::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too many requests, slow down",
+        )
+        .into_response()
+    };
+    insert_headers(response.headers_mut(), &decision, &rule);
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use axum_test::{TestResponse, TestServer};
+    use http::StatusCode;
+    use tokio::time::sleep;
+
+    use super::{MemoryRateLimitStore, RateLimitRule, RateLimitStore, RateLimiter, RouteLimit};
+    use crate::{
+        app::{AppState, router},
+        auth::{ApiKey, ApiKeys, LoginRequest, TokenService},
+        error::Problem,
+        repository::MemoryRepository,
+    };
+
+    fn server() -> (TestServer, AppState) {
+        let repository = Arc::new(MemoryRepository::new());
+        repository.add_user("rizki", "rahasia");
+
+        let limiter = RateLimiter::default()
+            .with_default(RateLimitRule::new(3, 60).unwrap())
+            .with_route(RouteLimit {
+                name: "login".to_string(),
+                prefix: "/api/users/login".to_string(),
+                capacity: 2,
+                period_secs: 60,
+            });
+        let state = AppState::new(
+            repository,
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        )
+        .with_rate_limiter(limiter)
+        .with_api_keys(ApiKeys::new([ApiKey {
+            name: "mobile".to_string(),
+            digest: ApiKeys::digest("key-1"),
+        }]));
+
+        (TestServer::new(router(state.clone())).unwrap(), state)
+    }
+
+    async fn login(server: &TestServer) -> TestResponse {
+        login_with_key(server, None).await
+    }
+
+    async fn login_with_key(server: &TestServer, key: Option<&str>) -> TestResponse {
+        let request = server.post("/api/users/login");
+        let request = match key {
+            Some(key) => request.add_header("X-API-Key", key),
+            None => request,
+        };
+
+        request
+            .json(&LoginRequest {
+                username: "rizki".to_string(),
+                password: "salah".to_string(),
+            })
+            .expect_failure()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_limit_login() {
+        let (server, _) = server();
+
+        let response = login(&server).await;
+        response.assert_status_unauthorized();
+        response.assert_header("RateLimit-Limit", "2");
+        response.assert_header("RateLimit-Remaining", "1");
+        response.assert_header("RateLimit-Policy", "2;w=60");
+
+        let response = login(&server).await;
+        response.assert_status_unauthorized();
+        response.assert_header("RateLimit-Remaining", "0");
+
+        let response = login(&server).await;
+        response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+        response.assert_header("Retry-After", "30");
+        response.assert_header("RateLimit-Reset", "60");
+        let problem: Problem = response.json();
+        assert_eq!(problem.status, 429);
+
+        server
+            .get("/api/categories")
+            .await
+            .assert_header("RateLimit-Limit", "3");
+    }
+
+    #[tokio::test]
+    async fn test_limit_per_client() {
+        let (server, state) = server();
+        let token = state.tokens.issue("rizki").unwrap();
+
+        for _ in 0..3 {
+            server.get("/api/categories").await.assert_status_ok();
+        }
+        server
+            .get("/api/categories")
+            .await
+            .assert_status(StatusCode::TOO_MANY_REQUESTS);
+
+        let response = server
+            .get("/api/categories")
+            .authorization_bearer(&token)
+            .await;
+        response.assert_status_ok();
+        response.assert_header("RateLimit-Remaining", "2");
+
+        let response = server
+            .get("/api/categories")
+            .add_header("X-API-Key", "key-1")
+            .await;
+        response.assert_status_ok();
+        response.assert_header("RateLimit-Remaining", "2");
+
+        let response = server
+            .get("/api/categories")
+            .add_header("X-API-Key", "key-2")
+            .await;
+        response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_rotating_api_keys_share_login_limit() {
+        let (server, _) = server();
+
+        for key in ["acak-1", "acak-2"] {
+            login_with_key(&server, Some(key))
+                .await
+                .assert_status_unauthorized();
+        }
+        login_with_key(&server, Some("acak-3"))
+            .await
+            .assert_status(StatusCode::TOO_MANY_REQUESTS);
+
+        login_with_key(&server, Some("key-1"))
+            .await
+            .assert_status_unauthorized();
+    }
+
+    #[test]
+    fn test_rule_rejects_zero() {
+        assert!(RateLimitRule::new(0, 60).is_err());
+        assert!(RateLimitRule::new(5, 0).is_err());
+
+        let limiter = RateLimiter::default().with_route(RouteLimit {
+            name: "login".to_string(),
+            prefix: "/api/users/login".to_string(),
+            capacity: 0,
+            period_secs: 60,
+        });
+        assert_eq!(limiter.rule_for("/api/users/login"), None);
+    }
+
+    #[test]
+    fn test_rule_for_route() {
+        let limiter = RateLimiter::default()
+            .with_default(RateLimitRule::new(3, 60).unwrap())
+            .with_route(RouteLimit {
+                name: "login".to_string(),
+                prefix: "/api/users/login".to_string(),
+                capacity: 2,
+                period_secs: 60,
+            });
+
+        let name = |path| limiter.rule_for(path).map(|(name, _)| name.to_string());
+        assert_eq!(name("/api/users/login"), Some("login".to_string()));
+        assert_eq!(name("/api/users/login/2fa"), Some("login".to_string()));
+        assert_eq!(name("/api/users/login-other"), Some("default".to_string()));
+        assert_eq!(name("/api/users/loginX"), Some("default".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_idle_buckets_are_swept() {
+        let store = MemoryRateLimitStore::new().with_sweep_interval(Duration::from_millis(500));
+        let rule = RateLimitRule::new(5, 1).unwrap();
+
+        for client in 1..=100 {
+            store.take(&format!("ip:{}", client), &rule).await.unwrap();
+        }
+        assert_eq!(store.buckets(), 100);
+
+        sleep(Duration::from_millis(1100)).await;
+        store.take("ip:new", &rule).await.unwrap();
+        assert_eq!(store.buckets(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_token_bucket_refill() {
+        let store = MemoryRateLimitStore::new();
+        let rule = RateLimitRule::new(1, 1).unwrap();
+
+        assert!(store.take("ip:1", &rule).await.unwrap().allowed);
+        let decision = store.take("ip:1", &rule).await.unwrap();
+        assert!(!decision.allowed);
+        assert!(decision.retry_after.unwrap() <= Duration::from_secs(1));
+        assert!(store.take("ip:2", &rule).await.unwrap().allowed);
+
+        sleep(Duration::from_millis(1100)).await;
+        assert!(store.take("ip:1", &rule).await.unwrap().allowed);
+    }
+}
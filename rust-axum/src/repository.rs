@@ -0,0 +1,577 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use sqlx::{Error, Pool, Postgres, pool::PoolConnection};
+
+use crate::{
+    auth::User,
+    metrics::Metrics,
+    model::{Brand, BrandRequest, Category, CategoryRequest},
+    pagination::ListQuery,
+    session::Session,
+};
+
+#[async_trait]
+pub trait CategoryRepository: Send + Sync {
+    async fn find_all(&self) -> Result<Vec<Category>, Error>;
+    async fn find_page(&self, query: &ListQuery<Category>) -> Result<(Vec<Category>, i64), Error>;
+    async fn find_by_id(&self, id: i32) -> Result<Option<Category>, Error>;
+    async fn create(&self, request: CategoryRequest) -> Result<Category, Error>;
+    async fn update(&self, id: i32, request: CategoryRequest) -> Result<Option<Category>, Error>;
+    async fn update_if(
+        &self,
+        expected: &Category,
+        request: CategoryRequest,
+    ) -> Result<Option<Category>, Error>;
+    async fn delete(&self, id: i32) -> Result<bool, Error>;
+    async fn delete_if(&self, expected: &Category) -> Result<bool, Error>;
+}
+
+#[async_trait]
+pub trait BrandRepository: Send + Sync {
+    async fn find_all(&self) -> Result<Vec<Brand>, Error>;
+    async fn find_page(&self, query: &ListQuery<Brand>) -> Result<(Vec<Brand>, i64), Error>;
+    async fn find_by_id(&self, id: i32) -> Result<Option<Brand>, Error>;
+    async fn create(&self, request: BrandRequest) -> Result<Brand, Error>;
+    async fn update(&self, id: i32, request: BrandRequest) -> Result<Option<Brand>, Error>;
+    async fn update_if(
+        &self,
+        expected: &Brand,
+        request: BrandRequest,
+    ) -> Result<Option<Brand>, Error>;
+    async fn delete(&self, id: i32) -> Result<bool, Error>;
+    async fn delete_if(&self, expected: &Brand) -> Result<bool, Error>;
+}
+
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>, Error>;
+}
+
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    async fn create_session(&self, session: Session) -> Result<(), Error>;
+    async fn find_session(&self, id: &str) -> Result<Option<Session>, Error>;
+    async fn touch_session(&self, id: &str, last_seen_at: NaiveDateTime) -> Result<(), Error>;
+    async fn delete_session(&self, id: &str) -> Result<bool, Error>;
+    async fn delete_expired_sessions(
+        &self,
+        idle_before: NaiveDateTime,
+        created_before: NaiveDateTime,
+    ) -> Result<u64, Error>;
+}
+
+#[derive(Default)]
+pub struct MemoryRepository {
+    categories: Mutex<Vec<Category>>,
+    brands: Mutex<Vec<Brand>>,
+    users: Mutex<Vec<User>>,
+    sessions: Mutex<Vec<Session>>,
+}
+
+impl MemoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_user(&self, username: &str, password: &str) {
+        self.users.lock().unwrap().push(User {
+            username: username.to_string(),
+            password: crate::auth::hash_password(password).unwrap(),
+        });
+    }
+}
+
+#[async_trait]
+impl CategoryRepository for MemoryRepository {
+    async fn find_all(&self) -> Result<Vec<Category>, Error> {
+        Ok(self.categories.lock().unwrap().clone())
+    }
+
+    async fn find_page(&self, query: &ListQuery<Category>) -> Result<(Vec<Category>, i64), Error> {
+        Ok(query.apply(self.categories.lock().unwrap().clone()))
+    }
+
+    async fn find_by_id(&self, id: i32) -> Result<Option<Category>, Error> {
+        let categories = self.categories.lock().unwrap();
+        Ok(categories
+            .iter()
+            .find(|category| category.id == id)
+            .cloned())
+    }
+
+    async fn create(&self, request: CategoryRequest) -> Result<Category, Error> {
+        let mut categories = self.categories.lock().unwrap();
+        let id = categories
+            .iter()
+            .map(|category| category.id)
+            .max()
+            .unwrap_or(0)
+            + 1;
+
+        let category = Category {
+            id,
+            name: request.name,
+            description: request.description,
+        };
+        categories.push(category.clone());
+
+        Ok(category)
+    }
+
+    async fn update(&self, id: i32, request: CategoryRequest) -> Result<Option<Category>, Error> {
+        let mut categories = self.categories.lock().unwrap();
+
+        Ok(categories
+            .iter_mut()
+            .find(|category| category.id == id)
+            .map(|category| {
+                category.name = request.name;
+                category.description = request.description;
+                category.clone()
+            }))
+    }
+
+    async fn update_if(
+        &self,
+        expected: &Category,
+        request: CategoryRequest,
+    ) -> Result<Option<Category>, Error> {
+        let mut categories = self.categories.lock().unwrap();
+
+        Ok(categories
+            .iter_mut()
+            .find(|category| *category == expected)
+            .map(|category| {
+                category.name = request.name;
+                category.description = request.description;
+                category.clone()
+            }))
+    }
+
+    async fn delete(&self, id: i32) -> Result<bool, Error> {
+        let mut categories = self.categories.lock().unwrap();
+        let total = categories.len();
+        categories.retain(|category| category.id != id);
+
+        Ok(categories.len() != total)
+    }
+
+    async fn delete_if(&self, expected: &Category) -> Result<bool, Error> {
+        let mut categories = self.categories.lock().unwrap();
+        let total = categories.len();
+        categories.retain(|category| category != expected);
+
+        Ok(categories.len() != total)
+    }
+}
+
+#[async_trait]
+impl BrandRepository for MemoryRepository {
+    async fn find_all(&self) -> Result<Vec<Brand>, Error> {
+        Ok(self.brands.lock().unwrap().clone())
+    }
+
+    async fn find_page(&self, query: &ListQuery<Brand>) -> Result<(Vec<Brand>, i64), Error> {
+        Ok(query.apply(self.brands.lock().unwrap().clone()))
+    }
+
+    async fn find_by_id(&self, id: i32) -> Result<Option<Brand>, Error> {
+        let brands = self.brands.lock().unwrap();
+        Ok(brands.iter().find(|brand| brand.id == id).cloned())
+    }
+
+    async fn create(&self, request: BrandRequest) -> Result<Brand, Error> {
+        let mut brands = self.brands.lock().unwrap();
+        let id = brands.iter().map(|brand| brand.id).max().unwrap_or(0) + 1;
+        let now = Utc::now().naive_utc();
+
+        let brand = Brand {
+            id,
+            name: request.name,
+            description: request.description,
+            created_at: now,
+            updated_at: now,
+        };
+        brands.push(brand.clone());
+
+        Ok(brand)
+    }
+
+    async fn update(&self, id: i32, request: BrandRequest) -> Result<Option<Brand>, Error> {
+        let mut brands = self.brands.lock().unwrap();
+
+        Ok(brands.iter_mut().find(|brand| brand.id == id).map(|brand| {
+            brand.name = request.name;
+            brand.description = request.description;
+            brand.updated_at = Utc::now().naive_utc();
+            brand.clone()
+        }))
+    }
+
+    async fn update_if(
+        &self,
+        expected: &Brand,
+        request: BrandRequest,
+    ) -> Result<Option<Brand>, Error> {
+        let mut brands = self.brands.lock().unwrap();
+
+        Ok(brands
+            .iter_mut()
+            .find(|brand| brand.id == expected.id && brand.updated_at == expected.updated_at)
+            .map(|brand| {
+                brand.name = request.name;
+                brand.description = request.description;
+                brand.updated_at = Utc::now().naive_utc();
+                brand.clone()
+            }))
+    }
+
+    async fn delete(&self, id: i32) -> Result<bool, Error> {
+        let mut brands = self.brands.lock().unwrap();
+        let total = brands.len();
+        brands.retain(|brand| brand.id != id);
+
+        Ok(brands.len() != total)
+    }
+
+    async fn delete_if(&self, expected: &Brand) -> Result<bool, Error> {
+        let mut brands = self.brands.lock().unwrap();
+        let total = brands.len();
+        brands.retain(|brand| brand.id != expected.id || brand.updated_at != expected.updated_at);
+
+        Ok(brands.len() != total)
+    }
+}
+
+#[async_trait]
+impl UserRepository for MemoryRepository {
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>, Error> {
+        let users = self.users.lock().unwrap();
+        Ok(users.iter().find(|user| user.username == username).cloned())
+    }
+}
+
+#[async_trait]
+impl SessionStore for MemoryRepository {
+    async fn create_session(&self, session: Session) -> Result<(), Error> {
+        self.sessions.lock().unwrap().push(session);
+        Ok(())
+    }
+
+    async fn find_session(&self, id: &str) -> Result<Option<Session>, Error> {
+        let sessions = self.sessions.lock().unwrap();
+        Ok(sessions.iter().find(|session| session.id == id).cloned())
+    }
+
+    async fn touch_session(&self, id: &str, last_seen_at: NaiveDateTime) -> Result<(), Error> {
+        let mut sessions = self.sessions.lock().unwrap();
+        if let Some(session) = sessions.iter_mut().find(|session| session.id == id) {
+            session.last_seen_at = last_seen_at;
+        }
+
+        Ok(())
+    }
+
+    async fn delete_session(&self, id: &str) -> Result<bool, Error> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let total = sessions.len();
+        sessions.retain(|session| session.id != id);
+
+        Ok(sessions.len() != total)
+    }
+
+    async fn delete_expired_sessions(
+        &self,
+        idle_before: NaiveDateTime,
+        created_before: NaiveDateTime,
+    ) -> Result<u64, Error> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let total = sessions.len();
+        sessions.retain(|session| {
+            session.last_seen_at >= idle_before && session.created_at >= created_before
+        });
+
+        Ok((total - sessions.len()) as u64)
+    }
+}
+
+pub struct PostgresRepository {
+    pool: Pool<Postgres>,
+    metrics: Option<Arc<Metrics>>,
+}
+
+impl PostgresRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self {
+            pool,
+            metrics: None,
+        }
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    async fn acquire(&self) -> Result<PoolConnection<Postgres>, Error> {
+        let start = Instant::now();
+        let connection = self.pool.acquire().await?;
+        if let Some(metrics) = &self.metrics {
+            metrics.observe_acquire(start);
+        }
+
+        Ok(connection)
+    }
+}
+
+#[async_trait]
+impl CategoryRepository for PostgresRepository {
+    async fn find_all(&self) -> Result<Vec<Category>, Error> {
+        sqlx::query_as("SELECT * FROM categories ORDER BY id")
+            .fetch_all(&mut *self.acquire().await?)
+            .await
+    }
+
+    async fn find_page(&self, query: &ListQuery<Category>) -> Result<(Vec<Category>, i64), Error> {
+        let items = query
+            .select_sql("categories")
+            .build_query_as()
+            .fetch_all(&mut *self.acquire().await?)
+            .await?;
+        let total = query
+            .count_sql("categories")
+            .build_query_scalar()
+            .fetch_one(&mut *self.acquire().await?)
+            .await?;
+
+        Ok((items, total))
+    }
+
+    async fn find_by_id(&self, id: i32) -> Result<Option<Category>, Error> {
+        sqlx::query_as("SELECT * FROM categories WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&mut *self.acquire().await?)
+            .await
+    }
+
+    async fn create(&self, request: CategoryRequest) -> Result<Category, Error> {
+        sqlx::query_as("INSERT INTO categories(name, description) VALUES($1, $2) RETURNING *;")
+            .bind(request.name)
+            .bind(request.description)
+            .fetch_one(&mut *self.acquire().await?)
+            .await
+    }
+
+    async fn update(&self, id: i32, request: CategoryRequest) -> Result<Option<Category>, Error> {
+        sqlx::query_as(
+            "UPDATE categories SET name = $2, description = $3 WHERE id = $1 RETURNING *;",
+        )
+        .bind(id)
+        .bind(request.name)
+        .bind(request.description)
+        .fetch_optional(&mut *self.acquire().await?)
+        .await
+    }
+
+    async fn update_if(
+        &self,
+        expected: &Category,
+        request: CategoryRequest,
+    ) -> Result<Option<Category>, Error> {
+        sqlx::query_as(
+            "UPDATE categories SET name = $2, description = $3 WHERE id = $1 AND name = $4 AND description IS NOT DISTINCT FROM $5 RETURNING *;",
+        )
+        .bind(expected.id)
+        .bind(request.name)
+        .bind(request.description)
+        .bind(&expected.name)
+        .bind(&expected.description)
+        .fetch_optional(&mut *self.acquire().await?)
+        .await
+    }
+
+    async fn delete(&self, id: i32) -> Result<bool, Error> {
+        let result = sqlx::query("DELETE FROM categories WHERE id = $1;")
+            .bind(id)
+            .execute(&mut *self.acquire().await?)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_if(&self, expected: &Category) -> Result<bool, Error> {
+        let result = sqlx::query(
+            "DELETE FROM categories WHERE id = $1 AND name = $2 AND description IS NOT DISTINCT FROM $3;",
+        )
+        .bind(expected.id)
+        .bind(&expected.name)
+        .bind(&expected.description)
+        .execute(&mut *self.acquire().await?)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+impl BrandRepository for PostgresRepository {
+    async fn find_all(&self) -> Result<Vec<Brand>, Error> {
+        sqlx::query_as("SELECT * FROM brands ORDER BY id")
+            .fetch_all(&mut *self.acquire().await?)
+            .await
+    }
+
+    async fn find_page(&self, query: &ListQuery<Brand>) -> Result<(Vec<Brand>, i64), Error> {
+        let items = query
+            .select_sql("brands")
+            .build_query_as()
+            .fetch_all(&mut *self.acquire().await?)
+            .await?;
+        let total = query
+            .count_sql("brands")
+            .build_query_scalar()
+            .fetch_one(&mut *self.acquire().await?)
+            .await?;
+
+        Ok((items, total))
+    }
+
+    async fn find_by_id(&self, id: i32) -> Result<Option<Brand>, Error> {
+        sqlx::query_as("SELECT * FROM brands WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&mut *self.acquire().await?)
+            .await
+    }
+
+    async fn create(&self, request: BrandRequest) -> Result<Brand, Error> {
+        sqlx::query_as(
+            "INSERT INTO brands(name, description, created_at, updated_at) VALUES($1, $2, $3, $3) RETURNING *;",
+        )
+        .bind(request.name)
+        .bind(request.description)
+        .bind(Utc::now().naive_utc())
+        .fetch_one(&mut *self.acquire().await?)
+        .await
+    }
+
+    async fn update(&self, id: i32, request: BrandRequest) -> Result<Option<Brand>, Error> {
+        sqlx::query_as(
+            "UPDATE brands SET name = $2, description = $3, updated_at = $4 WHERE id = $1 RETURNING *;",
+        )
+        .bind(id)
+        .bind(request.name)
+        .bind(request.description)
+        .bind(Utc::now().naive_utc())
+        .fetch_optional(&mut *self.acquire().await?)
+        .await
+    }
+
+    async fn update_if(
+        &self,
+        expected: &Brand,
+        request: BrandRequest,
+    ) -> Result<Option<Brand>, Error> {
+        sqlx::query_as(
+            "UPDATE brands SET name = $2, description = $3, updated_at = $4 WHERE id = $1 AND updated_at = $5 RETURNING *;",
+        )
+        .bind(expected.id)
+        .bind(request.name)
+        .bind(request.description)
+        .bind(Utc::now().naive_utc())
+        .bind(expected.updated_at)
+        .fetch_optional(&mut *self.acquire().await?)
+        .await
+    }
+
+    async fn delete(&self, id: i32) -> Result<bool, Error> {
+        let result = sqlx::query("DELETE FROM brands WHERE id = $1;")
+            .bind(id)
+            .execute(&mut *self.acquire().await?)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_if(&self, expected: &Brand) -> Result<bool, Error> {
+        let result = sqlx::query("DELETE FROM brands WHERE id = $1 AND updated_at = $2;")
+            .bind(expected.id)
+            .bind(expected.updated_at)
+            .execute(&mut *self.acquire().await?)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+impl UserRepository for PostgresRepository {
+    async fn find_by_username(&self, username: &str) -> Result<Option<User>, Error> {
+        sqlx::query_as("SELECT * FROM users WHERE username = $1")
+            .bind(username)
+            .fetch_optional(&mut *self.acquire().await?)
+            .await
+    }
+}
+
+#[async_trait]
+impl SessionStore for PostgresRepository {
+    async fn create_session(&self, session: Session) -> Result<(), Error> {
+        sqlx::query(
+            "INSERT INTO sessions(id, username, created_at, last_seen_at) VALUES($1, $2, $3, $4);",
+        )
+        .bind(session.id)
+        .bind(session.username)
+        .bind(session.created_at)
+        .bind(session.last_seen_at)
+        .execute(&mut *self.acquire().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_session(&self, id: &str) -> Result<Option<Session>, Error> {
+        sqlx::query_as("SELECT * FROM sessions WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&mut *self.acquire().await?)
+            .await
+    }
+
+    async fn touch_session(&self, id: &str, last_seen_at: NaiveDateTime) -> Result<(), Error> {
+        sqlx::query("UPDATE sessions SET last_seen_at = $2 WHERE id = $1;")
+            .bind(id)
+            .bind(last_seen_at)
+            .execute(&mut *self.acquire().await?)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_session(&self, id: &str) -> Result<bool, Error> {
+        let result = sqlx::query("DELETE FROM sessions WHERE id = $1;")
+            .bind(id)
+            .execute(&mut *self.acquire().await?)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_expired_sessions(
+        &self,
+        idle_before: NaiveDateTime,
+        created_before: NaiveDateTime,
+    ) -> Result<u64, Error> {
+        let result =
+            sqlx::query("DELETE FROM sessions WHERE last_seen_at < $1 OR created_at < $2;")
+                .bind(idle_before)
+                .bind(created_before)
+                .execute(&mut *self.acquire().await?)
+                .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,143 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::{FromRequestParts, Request},
+    middleware::Next,
+    response::Response,
+};
+use http::{HeaderName, HeaderValue, request::Parts};
+use uuid::Uuid;
+
+pub static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+const MAX_LENGTH: usize = 64;
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: RequestId;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    pub fn generate() -> Self {
+        Self(Uuid::now_v7().to_string())
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let well_formed = !value.is_empty()
+            && value.len() <= MAX_LENGTH
+            && value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+
+        well_formed.then(|| Self(value.to_string()))
+    }
+
+    pub fn current() -> Option<Self> {
+        CURRENT_REQUEST_ID.try_with(|id| id.clone()).ok()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for RequestId {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<RequestId>()
+            .cloned()
+            .unwrap_or_else(RequestId::generate))
+    }
+}
+
+pub async fn request_id_middleware(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(&X_REQUEST_ID)
+        .and_then(|value| value.to_str().ok())
+        .and_then(RequestId::parse)
+        .unwrap_or_else(RequestId::generate);
+
+    let header = HeaderValue::from_str(request_id.as_str()).unwrap();
+    request
+        .headers_mut()
+        .insert(X_REQUEST_ID.clone(), header.clone());
+    request.extensions_mut().insert(request_id.clone());
+
+    let mut response = CURRENT_REQUEST_ID
+        .scope(request_id, next.run(request))
+        .await;
+    response.headers_mut().insert(X_REQUEST_ID.clone(), header);
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, middleware::from_fn, routing::get};
+    use axum_test::TestServer;
+    use uuid::Uuid;
+
+    use super::{RequestId, request_id_middleware};
+
+    fn server() -> TestServer {
+        async fn route(request_id: RequestId) -> String {
+            request_id.to_string()
+        }
+
+        let app = Router::new()
+            .route("/get", get(route))
+            .layer(from_fn(request_id_middleware));
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_generate_request_id() {
+        let server = server();
+
+        let first = server.get("/get").await;
+        let second = server.get("/get").await;
+
+        let id = Uuid::parse_str(&first.text()).unwrap();
+        assert_eq!(id.get_version_num(), 7);
+        first.assert_header("X-Request-ID", first.text());
+        assert_ne!(first.text(), second.text());
+    }
+
+    #[tokio::test]
+    async fn test_propagate_request_id() {
+        let server = server();
+
+        let response = server
+            .get("/get")
+            .add_header("X-Request-ID", "upstream-id.123_abc")
+            .await;
+        response.assert_text("upstream-id.123_abc");
+        response.assert_header("X-Request-ID", "upstream-id.123_abc");
+    }
+
+    #[tokio::test]
+    async fn test_reject_malformed_request_id() {
+        let server = server();
+
+        for malformed in ["", "has space", "line\tbreak", &"a".repeat(65)] {
+            let response = server
+                .get("/get")
+                .add_header("X-Request-ID", malformed)
+                .await;
+            assert_ne!(response.text(), malformed);
+            assert!(Uuid::parse_str(&response.text()).is_ok());
+        }
+    }
+}
@@ -0,0 +1,478 @@
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use axum::{
+    Router,
+    body::Body,
+    extract::{Request, State},
+    middleware::{Next, from_fn_with_state},
+    response::{IntoResponse, Response},
+};
+use http::{
+    HeaderMap, HeaderName, HeaderValue, Method, StatusCode,
+    header::{
+        CONTENT_LENGTH, CONTENT_SECURITY_POLICY, CONTENT_TYPE, STRICT_TRANSPORT_SECURITY,
+        X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS,
+    },
+};
+use http_body_util::Limited;
+use serde::Deserialize;
+use tower_http::{
+    cors::{AllowOrigin, Any, CorsLayer},
+    set_header::SetResponseHeaderLayer,
+};
+
+use crate::error::AppError;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SecuritySettings {
+    pub cors: CorsSettings,
+    pub headers: SecurityHeaders,
+    pub body_limits: BodyLimits,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CorsSettings {
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    pub exposed_headers: Vec<String>,
+    pub allow_credentials: bool,
+    pub max_age_secs: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SecurityHeaders {
+    pub hsts_max_age_secs: u64,
+    pub hsts_include_subdomains: bool,
+    pub frame_options: String,
+    pub content_security_policy: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct BodyLimits {
+    pub json: usize,
+    pub form: usize,
+    pub multipart: usize,
+    pub default: usize,
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+impl Default for CorsSettings {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_methods: strings(&["GET", "POST", "PUT", "PATCH", "DELETE"]),
+            allowed_headers: strings(&[
+                "authorization",
+                "content-type",
+                "x-api-key",
+                "idempotency-key",
+                "if-match",
+                "if-none-match",
+                "if-modified-since",
+                "if-unmodified-since",
+            ]),
+            exposed_headers: strings(&[
+                "x-request-id",
+                "etag",
+                "last-modified",
+                "retry-after",
+                "ratelimit-limit",
+                "ratelimit-remaining",
+                "ratelimit-reset",
+                "ratelimit-policy",
+                "idempotent-replayed",
+            ]),
+            allow_credentials: false,
+            max_age_secs: 600,
+        }
+    }
+}
+
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        Self {
+            hsts_max_age_secs: 31_536_000,
+            hsts_include_subdomains: true,
+            frame_options: "DENY".to_string(),
+            content_security_policy:
+                "default-src 'self'; frame-ancestors 'none'; object-src 'none'".to_string(),
+        }
+    }
+}
+
+impl Default for BodyLimits {
+    fn default() -> Self {
+        Self {
+            json: 1024 * 1024,
+            form: 64 * 1024,
+            multipart: 8 * 1024 * 1024,
+            default: 2 * 1024 * 1024,
+        }
+    }
+}
+
+fn parse_all<T: FromStr>(kind: &str, values: &[String]) -> Result<Vec<T>, String> {
+    values
+        .iter()
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| format!("invalid CORS {} `{}`", kind, value))
+        })
+        .collect()
+}
+
+fn is_wildcard(values: &[String]) -> bool {
+    values.iter().any(|value| value == "*")
+}
+
+impl CorsSettings {
+    pub fn layer(&self) -> Result<Option<CorsLayer>, String> {
+        if self.allowed_origins.is_empty() {
+            return Ok(None);
+        }
+
+        if self.allow_credentials
+            && [
+                &self.allowed_origins,
+                &self.allowed_methods,
+                &self.allowed_headers,
+                &self.exposed_headers,
+            ]
+            .into_iter()
+            .any(|values| is_wildcard(values))
+        {
+            return Err("CORS credentials can not be combined with a `*` wildcard".to_string());
+        }
+
+        let mut layer = CorsLayer::new()
+            .allow_credentials(self.allow_credentials)
+            .max_age(Duration::from_secs(self.max_age_secs));
+
+        layer = if is_wildcard(&self.allowed_origins) {
+            layer.allow_origin(Any)
+        } else {
+            layer.allow_origin(AllowOrigin::list(parse_all::<HeaderValue>(
+                "origin",
+                &self.allowed_origins,
+            )?))
+        };
+        layer = if is_wildcard(&self.allowed_methods) {
+            layer.allow_methods(Any)
+        } else {
+            layer.allow_methods(parse_all::<Method>("method", &self.allowed_methods)?)
+        };
+        layer = if is_wildcard(&self.allowed_headers) {
+            layer.allow_headers(Any)
+        } else {
+            layer.allow_headers(parse_all::<HeaderName>("header", &self.allowed_headers)?)
+        };
+        layer = if is_wildcard(&self.exposed_headers) {
+            layer.expose_headers(Any)
+        } else {
+            layer.expose_headers(parse_all::<HeaderName>("header", &self.exposed_headers)?)
+        };
+
+        Ok(Some(layer))
+    }
+}
+
+impl SecurityHeaders {
+    pub fn values(&self) -> Result<Vec<(HeaderName, HeaderValue)>, String> {
+        let mut headers = vec![(X_CONTENT_TYPE_OPTIONS, "nosniff".to_string())];
+
+        if self.hsts_max_age_secs > 0 {
+            let mut hsts = format!("max-age={}", self.hsts_max_age_secs);
+            if self.hsts_include_subdomains {
+                hsts.push_str("; includeSubDomains");
+            }
+            headers.push((STRICT_TRANSPORT_SECURITY, hsts));
+        }
+        if !self.frame_options.is_empty() {
+            headers.push((X_FRAME_OPTIONS, self.frame_options.clone()));
+        }
+        if !self.content_security_policy.is_empty() {
+            headers.push((
+                CONTENT_SECURITY_POLICY,
+                self.content_security_policy.clone(),
+            ));
+        }
+
+        headers
+            .into_iter()
+            .map(|(name, value)| {
+                HeaderValue::from_str(&value)
+                    .map(|value| (name.clone(), value))
+                    .map_err(|_| format!("invalid value for header {}", name))
+            })
+            .collect()
+    }
+}
+
+impl BodyLimits {
+    pub fn limit_for(&self, headers: &HeaderMap) -> usize {
+        let essence = headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .unwrap_or("")
+            .trim()
+            .to_ascii_lowercase();
+
+        if essence == "application/json"
+            || (essence.starts_with("application/") && essence.ends_with("+json"))
+        {
+            self.json
+        } else if essence == "application/x-www-form-urlencoded" {
+            self.form
+        } else if essence.starts_with("multipart/") {
+            self.multipart
+        } else {
+            self.default
+        }
+    }
+}
+
+impl SecuritySettings {
+    pub fn validate(&self) -> Result<(), String> {
+        self.cors.layer()?;
+        self.headers.values()?;
+
+        Ok(())
+    }
+}
+
+pub async fn body_limit_middleware(
+    State(limits): State<Arc<BodyLimits>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let limit = limits.limit_for(request.headers());
+    let length = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+
+    if length.is_some_and(|length| length > limit as u64) {
+        return AppError::new(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("Request body is larger than {} bytes", limit),
+        )
+        .with_extension("limit", limit)
+        .into_response();
+    }
+
+    let request = request.map(|body| Body::new(Limited::new(body, limit)));
+    next.run(request).await
+}
+
+pub fn layers<S>(router: Router<S>, settings: &SecuritySettings) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let router = router.layer(from_fn_with_state(
+        Arc::new(settings.body_limits),
+        body_limit_middleware,
+    ));
+
+    let router = match settings.cors.layer() {
+        Ok(Some(cors)) => router.layer(cors),
+        Ok(None) => router,
+        Err(err) => {
+            eprintln!("CORS is disabled : {}", err);
+            router
+        }
+    };
+
+    let headers = settings.headers.values().unwrap_or_else(|err| {
+        eprintln!("Security headers are disabled : {}", err);
+        Vec::new()
+    });
+    headers.into_iter().fold(router, |router, (name, value)| {
+        router.layer(SetResponseHeaderLayer::if_not_present(name, value))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        routing::post,
+    };
+    use axum_test::TestServer;
+    use http::{Method, Request, StatusCode, header};
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    use super::{BodyLimits, CorsSettings, SecuritySettings};
+    use crate::{
+        app::{AppState, router},
+        auth::TokenService,
+        error::Problem,
+        repository::MemoryRepository,
+    };
+
+    fn settings() -> SecuritySettings {
+        SecuritySettings {
+            cors: CorsSettings {
+                allowed_origins: vec!["https://app.example.com".to_string()],
+                allow_credentials: true,
+                ..CorsSettings::default()
+            },
+            body_limits: BodyLimits {
+                json: 64,
+                form: 16,
+                ..BodyLimits::default()
+            },
+            ..SecuritySettings::default()
+        }
+    }
+
+    fn app() -> Router {
+        let state = AppState::new(
+            Arc::new(MemoryRepository::new()),
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        )
+        .with_security(settings());
+
+        router(state)
+    }
+
+    #[tokio::test]
+    async fn test_cors_preflight() {
+        let server = TestServer::new(app()).unwrap();
+
+        let response = server
+            .method(Method::OPTIONS, "/api/categories")
+            .add_header("Origin", "https://app.example.com")
+            .add_header("Access-Control-Request-Method", "POST")
+            .add_header("Access-Control-Request-Headers", "content-type")
+            .await;
+        response.assert_status_ok();
+        response.assert_header("Access-Control-Allow-Origin", "https://app.example.com");
+        response.assert_header("Access-Control-Allow-Credentials", "true");
+        response.assert_header("Access-Control-Max-Age", "600");
+        let methods = response.header("Access-Control-Allow-Methods");
+        assert!(methods.to_str().unwrap().contains("POST"));
+
+        let response = server
+            .method(Method::OPTIONS, "/api/categories")
+            .add_header("Origin", "https://evil.example.com")
+            .add_header("Access-Control-Request-Method", "POST")
+            .await;
+        assert!(
+            response
+                .maybe_header("Access-Control-Allow-Origin")
+                .is_none()
+        );
+
+        let response = server
+            .get("/api/categories")
+            .add_header("Origin", "https://app.example.com")
+            .await;
+        response.assert_status_ok();
+        response.assert_header("Access-Control-Allow-Origin", "https://app.example.com");
+        let exposed = response.header("Access-Control-Expose-Headers");
+        assert!(exposed.to_str().unwrap().contains("x-request-id"));
+    }
+
+    #[tokio::test]
+    async fn test_security_headers() {
+        let server = TestServer::new(app()).unwrap();
+
+        for response in [
+            server.get("/api/categories").await,
+            server.get("/tidak-ada").expect_failure().await,
+        ] {
+            response.assert_header("X-Content-Type-Options", "nosniff");
+            response.assert_header("X-Frame-Options", "DENY");
+            response.assert_header(
+                "Strict-Transport-Security",
+                "max-age=31536000; includeSubDomains",
+            );
+            response.assert_header(
+                "Content-Security-Policy",
+                "default-src 'self'; frame-ancestors 'none'; object-src 'none'",
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_body_limits() {
+        let server = TestServer::new(app()).unwrap();
+
+        server
+            .post("/api/categories")
+            .json(&json!({ "name": "Gadget" }))
+            .await
+            .assert_status(StatusCode::CREATED);
+
+        let response = server
+            .post("/api/categories")
+            .json(&json!({ "name": "x".repeat(100) }))
+            .await;
+        response.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+        response.assert_header("Content-Type", "application/problem+json");
+    }
+
+    #[tokio::test]
+    async fn test_body_limit_layer() {
+        let app = super::layers(
+            Router::new().route("/echo", post(|body: String| async move { body })),
+            &settings(),
+        );
+
+        let request = |content_type: &str, body: String| {
+            Request::post("/echo")
+                .header(header::CONTENT_TYPE, content_type)
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request("application/json", "{}".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .oneshot(request("application/json", "x".repeat(100)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "application/x-www-form-urlencoded",
+                "name=Gadget&description=Semua".to_string(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let mut declared = request("application/json", "x".repeat(100));
+        declared
+            .headers_mut()
+            .insert(header::CONTENT_LENGTH, "100".parse().unwrap());
+        let response = app.oneshot(declared).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let problem: Problem = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem.extensions["limit"], 64);
+    }
+}
@@ -0,0 +1,376 @@
+use std::{sync::Arc, time::Duration};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use axum::extract::{FromRef, FromRequestParts, State};
+use axum_extra::extract::cookie::{Cookie, Key, PrivateCookieJar, SameSite};
+use chrono::{NaiveDateTime, Utc};
+use http::{StatusCode, request::Parts};
+use sqlx::prelude::FromRow;
+use tokio::time::interval;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::{
+    app::AppState,
+    auth::{LoginRequest, verify_password},
+    error::{AppError, Problem},
+    extract::ValidatedJson,
+    repository::SessionStore,
+    shutdown::ShutdownSignal,
+};
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Session {
+    pub id: String,
+    pub username: String,
+    pub created_at: NaiveDateTime,
+    pub last_seen_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone)]
+pub struct SessionSettings {
+    pub cookie_name: String,
+    pub idle_timeout: Duration,
+    pub absolute_timeout: Duration,
+    pub secure: bool,
+}
+
+impl Default for SessionSettings {
+    fn default() -> Self {
+        Self {
+            cookie_name: "session_id".to_string(),
+            idle_timeout: Duration::from_secs(30 * 60),
+            absolute_timeout: Duration::from_secs(24 * 60 * 60),
+            secure: false,
+        }
+    }
+}
+
+fn subtract(now: NaiveDateTime, timeout: Duration) -> NaiveDateTime {
+    chrono::Duration::from_std(timeout)
+        .ok()
+        .and_then(|timeout| now.checked_sub_signed(timeout))
+        .unwrap_or(NaiveDateTime::MIN)
+}
+
+impl SessionSettings {
+    fn idle_before(&self, now: NaiveDateTime) -> NaiveDateTime {
+        subtract(now, self.idle_timeout)
+    }
+
+    fn created_before(&self, now: NaiveDateTime) -> NaiveDateTime {
+        subtract(now, self.absolute_timeout)
+    }
+
+    fn cookie(&self, value: String) -> Cookie<'static> {
+        Cookie::build((self.cookie_name.clone(), value))
+            .path("/")
+            .http_only(true)
+            .same_site(SameSite::Lax)
+            .secure(self.secure)
+            .build()
+    }
+}
+
+impl Session {
+    pub fn new(username: &str) -> Self {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        let now = Utc::now().naive_utc();
+
+        Self {
+            id: bytes.iter().map(|byte| format!("{:02x}", byte)).collect(),
+            username: username.to_string(),
+            created_at: now,
+            last_seen_at: now,
+        }
+    }
+
+    pub fn is_expired(&self, now: NaiveDateTime, settings: &SessionSettings) -> bool {
+        self.last_seen_at < settings.idle_before(now)
+            || self.created_at < settings.created_before(now)
+    }
+}
+
+impl FromRef<AppState> for Key {
+    fn from_ref(state: &AppState) -> Self {
+        state.cookie_key.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SessionUser {
+    pub session_id: String,
+    pub username: String,
+}
+
+impl FromRequestParts<AppState> for SessionUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let jar = PrivateCookieJar::<Key>::from_request_parts(parts, state)
+            .await
+            .unwrap_or_else(|err| match err {});
+        let id = jar
+            .get(&state.session_settings.cookie_name)
+            .map(|cookie| cookie.value().to_string())
+            .ok_or_else(|| AppError::unauthorized("Missing session"))?;
+
+        let session = state
+            .sessions
+            .find_session(&id)
+            .await?
+            .ok_or_else(|| AppError::unauthorized("Invalid or expired session"))?;
+
+        let now = Utc::now().naive_utc();
+        if session.is_expired(now, &state.session_settings) {
+            state.sessions.delete_session(&id).await?;
+            return Err(AppError::unauthorized("Invalid or expired session"));
+        }
+
+        state.sessions.touch_session(&id, now).await?;
+
+        Ok(SessionUser {
+            session_id: session.id,
+            username: session.username,
+        })
+    }
+}
+
+pub async fn cleanup_expired_sessions(
+    sessions: Arc<dyn SessionStore>,
+    settings: SessionSettings,
+    period: Duration,
+    mut signal: ShutdownSignal,
+) {
+    let mut ticker = interval(period);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                let now = Utc::now().naive_utc();
+                if let Err(err) = sessions
+                    .delete_expired_sessions(settings.idle_before(now), settings.created_before(now))
+                    .await
+                {
+                    eprintln!("Failed to delete expired sessions : {}", err);
+                }
+            }
+            _ = signal.wait() => break,
+        }
+    }
+}
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(login, logout))
+        .routes(routes!(current))
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "sessions",
+    request_body = LoginRequest,
+    responses(
+        (status = 204, description = "Session is created and its cookie is set"),
+        (status = 401, description = "Username or password is wrong", body = Problem, content_type = "application/problem+json")
+    )
+)]
+async fn login(
+    State(state): State<AppState>,
+    jar: PrivateCookieJar,
+    ValidatedJson(request): ValidatedJson<LoginRequest>,
+) -> Result<(StatusCode, PrivateCookieJar), AppError> {
+    let user = state.users.find_by_username(&request.username).await?;
+
+    match user {
+        Some(user) if verify_password(&request.password, &user.password) => {
+            let settings = &state.session_settings;
+            if let Some(previous) = jar.get(&settings.cookie_name) {
+                state.sessions.delete_session(previous.value()).await?;
+            }
+
+            let session = Session::new(&user.username);
+            let cookie = settings.cookie(session.id.clone());
+            state.sessions.create_session(session).await?;
+
+            Ok((StatusCode::NO_CONTENT, jar.add(cookie)))
+        }
+        _ => Err(AppError::unauthorized("Username or password is wrong")),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/",
+    tag = "sessions",
+    security(("session" = [])),
+    responses((status = 204, description = "Session is deleted and its cookie is removed"))
+)]
+async fn logout(
+    State(state): State<AppState>,
+    jar: PrivateCookieJar,
+) -> Result<(StatusCode, PrivateCookieJar), AppError> {
+    let settings = &state.session_settings;
+    if let Some(cookie) = jar.get(&settings.cookie_name) {
+        state.sessions.delete_session(cookie.value()).await?;
+    }
+
+    Ok((
+        StatusCode::NO_CONTENT,
+        jar.remove(settings.cookie(String::new())),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/current",
+    tag = "sessions",
+    security(("session" = [])),
+    responses(
+        (status = 200, body = String, content_type = "text/plain"),
+        (status = 401, description = "Session is missing, invalid or expired", body = Problem, content_type = "application/problem+json")
+    )
+)]
+async fn current(user: SessionUser) -> String {
+    format!("Hello {}", user.username)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use axum_extra::extract::cookie::{Cookie, Key};
+    use axum_test::TestServer;
+    use http::StatusCode;
+    use tokio::time::sleep;
+
+    use super::SessionSettings;
+    use crate::{
+        app::{AppState, router},
+        auth::{LoginRequest, TokenService},
+        error::Problem,
+        repository::MemoryRepository,
+    };
+
+    fn server(settings: SessionSettings) -> TestServer {
+        let repository = Arc::new(MemoryRepository::new());
+        repository.add_user("rizki", "rahasia");
+
+        let state = AppState::new(
+            repository,
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        )
+        .with_sessions(Key::generate(), settings);
+        TestServer::new(router(state)).unwrap()
+    }
+
+    async fn login(server: &TestServer, previous: Option<Cookie<'static>>) -> Cookie<'static> {
+        let mut request = server.post("/api/sessions").json(&LoginRequest {
+            username: "rizki".to_string(),
+            password: "rahasia".to_string(),
+        });
+        if let Some(previous) = previous {
+            request = request.add_cookie(previous);
+        }
+
+        let response = request.await;
+        response.assert_status(StatusCode::NO_CONTENT);
+        response.cookie("session_id")
+    }
+
+    async fn current(server: &TestServer, cookie: Cookie<'static>) -> axum_test::TestResponse {
+        server
+            .get("/api/sessions/current")
+            .add_cookie(cookie)
+            .expect_failure()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_session_login() {
+        let server = server(SessionSettings::default());
+
+        let cookie = login(&server, None).await;
+        assert_eq!(cookie.http_only(), Some(true));
+        assert_eq!(cookie.path(), Some("/"));
+
+        let response = server.get("/api/sessions/current").add_cookie(cookie).await;
+        response.assert_status_ok();
+        response.assert_text("Hello rizki");
+
+        let response = current(&server, Cookie::new("session_id", "rizki")).await;
+        response.assert_status_unauthorized();
+        let problem: Problem = response.json();
+        assert_eq!(problem.detail, "Missing session");
+
+        let response = server
+            .post("/api/sessions")
+            .json(&LoginRequest {
+                username: "rizki".to_string(),
+                password: "salah".to_string(),
+            })
+            .await;
+        response.assert_status_unauthorized();
+    }
+
+    #[tokio::test]
+    async fn test_session_rotation_and_logout() {
+        let server = server(SessionSettings::default());
+
+        let first = login(&server, None).await;
+        let second = login(&server, Some(first.clone())).await;
+        assert_ne!(first.value(), second.value());
+
+        current(&server, first).await.assert_status_unauthorized();
+        server
+            .get("/api/sessions/current")
+            .add_cookie(second.clone())
+            .await
+            .assert_status_ok();
+
+        let response = server
+            .delete("/api/sessions")
+            .add_cookie(second.clone())
+            .await;
+        response.assert_status(StatusCode::NO_CONTENT);
+        assert_eq!(response.cookie("session_id").value(), "");
+
+        let response = current(&server, second).await;
+        response.assert_status_unauthorized();
+        let problem: Problem = response.json();
+        assert_eq!(problem.detail, "Invalid or expired session");
+    }
+
+    #[tokio::test]
+    async fn test_session_expiry() {
+        let idle = server(SessionSettings {
+            idle_timeout: Duration::from_millis(50),
+            ..SessionSettings::default()
+        });
+        let cookie = login(&idle, None).await;
+        sleep(Duration::from_millis(100)).await;
+        current(&idle, cookie).await.assert_status_unauthorized();
+
+        let absolute = server(SessionSettings {
+            absolute_timeout: Duration::from_millis(150),
+            ..SessionSettings::default()
+        });
+        let cookie = login(&absolute, None).await;
+        for _ in 0..2 {
+            sleep(Duration::from_millis(50)).await;
+            absolute
+                .get("/api/sessions/current")
+                .add_cookie(cookie.clone())
+                .await
+                .assert_status_ok();
+        }
+        sleep(Duration::from_millis(100)).await;
+        current(&absolute, cookie)
+            .await
+            .assert_status_unauthorized();
+    }
+}
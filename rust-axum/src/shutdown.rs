@@ -0,0 +1,320 @@
+use std::{future::Future, io, net::SocketAddr, sync::Arc, time::Duration};
+
+use axum::{Router, extract::ConnectInfo};
+use http::Request;
+use hyper::{body::Incoming, server::conn::http1};
+use hyper_util::{rt::TokioIo, service::TowerToHyperService};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::watch,
+    task::{JoinHandle, JoinSet},
+    time::{sleep, timeout},
+};
+use tower::ServiceExt;
+
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ShutdownSignal(watch::Receiver<bool>);
+
+impl ShutdownSignal {
+    pub fn is_shutdown(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    pub async fn wait(&mut self) {
+        let _ = self.0.wait_for(|shutdown| *shutdown).await;
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Shutdown(Arc<watch::Sender<bool>>);
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        let (sender, _) = watch::channel(false);
+        Self(Arc::new(sender))
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn signal(&self) -> ShutdownSignal {
+        ShutdownSignal(self.0.subscribe())
+    }
+
+    pub fn trigger(&self) {
+        self.0.send_replace(true);
+    }
+}
+
+struct BackgroundTask {
+    name: String,
+    sender: watch::Sender<bool>,
+    handle: JoinHandle<()>,
+}
+
+#[derive(Default)]
+pub struct BackgroundTasks {
+    tasks: Vec<BackgroundTask>,
+}
+
+impl BackgroundTasks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn spawn<F, Fut>(&mut self, name: &str, task: F)
+    where
+        F: FnOnce(ShutdownSignal) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let (sender, receiver) = watch::channel(false);
+        let handle = tokio::spawn(task(ShutdownSignal(receiver)));
+
+        self.tasks.push(BackgroundTask {
+            name: name.to_string(),
+            sender,
+            handle,
+        });
+    }
+
+    pub async fn close(self, deadline: Duration) {
+        for task in self.tasks {
+            let _ = task.sender.send(true);
+
+            let mut handle = task.handle;
+            if timeout(deadline, &mut handle).await.is_err() {
+                eprintln!(
+                    "Background task {} did not stop within {:?}, aborting",
+                    task.name, deadline
+                );
+                handle.abort();
+            }
+        }
+    }
+}
+
+async fn serve_connection(
+    stream: TcpStream,
+    client: SocketAddr,
+    app: Router,
+    mut shutdown: ShutdownSignal,
+) {
+    let service = app.map_request(move |mut request: Request<Incoming>| {
+        request.extensions_mut().insert(ConnectInfo(client));
+        request
+    });
+    let connection = http1::Builder::new()
+        .serve_connection(TokioIo::new(stream), TowerToHyperService::new(service))
+        .with_upgrades();
+    tokio::pin!(connection);
+
+    tokio::select! {
+        _ = connection.as_mut() => {}
+        _ = shutdown.wait() => {
+            connection.as_mut().graceful_shutdown();
+            let _ = connection.await;
+        }
+    }
+}
+
+pub async fn serve_with_shutdown<F>(
+    listener: TcpListener,
+    app: Router,
+    signal: F,
+    drain_timeout: Duration,
+) -> io::Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let (sender, receiver) = watch::channel(false);
+    let mut connections = JoinSet::new();
+    tokio::pin!(signal);
+
+    loop {
+        let (stream, client) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(err) => {
+                    eprintln!("Failed to accept connection : {}", err);
+                    sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            },
+            Some(_) = connections.join_next() => continue,
+            _ = &mut signal => break,
+        };
+
+        connections.spawn(serve_connection(
+            stream,
+            client,
+            app.clone(),
+            ShutdownSignal(receiver.clone()),
+        ));
+    }
+
+    drop(listener);
+    let _ = sender.send(true);
+
+    let drained = timeout(drain_timeout, async {
+        while connections.join_next().await.is_some() {}
+    })
+    .await;
+    if drained.is_err() {
+        eprintln!(
+            "{} connections did not drain within {:?}, closing them",
+            connections.len(),
+            drain_timeout
+        );
+        connections.shutdown().await;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+
+    use axum::{Router, routing::get};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+        sync::oneshot,
+        time::{sleep, timeout},
+    };
+
+    use super::{BackgroundTasks, serve_with_shutdown};
+
+    async fn slow_server(
+        delay: Duration,
+        drain_timeout: Duration,
+    ) -> (
+        std::net::SocketAddr,
+        oneshot::Sender<()>,
+        tokio::task::JoinHandle<std::io::Result<()>>,
+    ) {
+        let app = Router::new().route(
+            "/slow",
+            get(move || async move {
+                sleep(delay).await;
+                "done"
+            }),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (trigger, signal) = oneshot::channel::<()>();
+
+        let server = tokio::spawn(serve_with_shutdown(
+            listener,
+            app,
+            async move {
+                let _ = signal.await;
+            },
+            drain_timeout,
+        ));
+
+        (address, trigger, server)
+    }
+
+    async fn get_slow(address: std::net::SocketAddr) -> String {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream
+            .write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+
+        let mut response = String::new();
+        let _ = stream.read_to_string(&mut response).await;
+        response
+    }
+
+    #[tokio::test]
+    async fn test_drain_in_flight_request() {
+        let (address, trigger, server) =
+            slow_server(Duration::from_millis(300), Duration::from_secs(5)).await;
+
+        let request = tokio::spawn(get_slow(address));
+        sleep(Duration::from_millis(100)).await;
+        trigger.send(()).unwrap();
+
+        let response = request.await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("done"));
+
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_drain_deadline() {
+        let (address, trigger, server) =
+            slow_server(Duration::from_secs(10), Duration::from_millis(200)).await;
+
+        let request = tokio::spawn(get_slow(address));
+        sleep(Duration::from_millis(100)).await;
+
+        let start = Instant::now();
+        trigger.send(()).unwrap();
+        server.await.unwrap().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(2));
+
+        let response = timeout(Duration::from_secs(1), request)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(response.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_close_background_tasks_in_order() {
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let mut tasks = BackgroundTasks::new();
+
+        for name in ["first", "second", "third"] {
+            let closed = closed.clone();
+            tasks.spawn(name, move |mut signal| async move {
+                signal.wait().await;
+                closed.lock().unwrap().push(name);
+            });
+        }
+
+        tasks.close(Duration::from_secs(1)).await;
+        assert_eq!(*closed.lock().unwrap(), vec!["first", "second", "third"]);
+
+        let mut tasks = BackgroundTasks::new();
+        tasks.spawn("stuck", |_| std::future::pending::<()>());
+        let start = Instant::now();
+        tasks.close(Duration::from_millis(100)).await;
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
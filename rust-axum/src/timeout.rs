@@ -0,0 +1,277 @@
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http::{StatusCode, request::Parts};
+use serde::Deserialize;
+use tokio::time::timeout;
+
+use crate::{app::AppState, error::AppError};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RouteTimeout {
+    pub prefix: String,
+    pub timeout_ms: u64,
+}
+
+pub struct Timeouts {
+    default: Option<Duration>,
+    routes: Vec<RouteTimeout>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self::new().with_default(Duration::from_secs(30))
+    }
+}
+
+impl Timeouts {
+    pub fn new() -> Self {
+        Self {
+            default: None,
+            routes: Vec::new(),
+        }
+    }
+
+    pub fn with_default(mut self, timeout: Duration) -> Self {
+        self.default = Some(timeout);
+        self
+    }
+
+    pub fn with_route(mut self, route: RouteTimeout) -> Self {
+        self.routes.push(route);
+        self.routes
+            .sort_by_key(|route| std::cmp::Reverse(route.prefix.len()));
+        self
+    }
+
+    pub fn longest(&self) -> Option<Duration> {
+        self.routes
+            .iter()
+            .map(|route| Duration::from_millis(route.timeout_ms))
+            .chain(self.default)
+            .max()
+    }
+
+    pub fn timeout_for(&self, path: &str) -> Option<Duration> {
+        self.routes
+            .iter()
+            .find(|route| matches_prefix(path, &route.prefix))
+            .map(|route| Duration::from_millis(route.timeout_ms))
+            .or(self.default)
+    }
+}
+
+pub fn matches_prefix(path: &str, prefix: &str) -> bool {
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/') || prefix.ends_with('/'))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Deadline {
+    started: Instant,
+    budget: Duration,
+}
+
+impl Deadline {
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            started: Instant::now(),
+            budget,
+        }
+    }
+
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.budget.saturating_sub(self.started.elapsed())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    pub async fn run<F: Future>(&self, future: F) -> Result<F::Output, AppError> {
+        timeout(self.remaining(), future).await.map_err(|_| {
+            AppError::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "A dependency did not respond within the request deadline",
+            )
+            .with_extension("timeout_ms", self.budget.as_millis() as u64)
+        })
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Deadline {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Deadline>()
+            .copied()
+            .ok_or_else(|| AppError::internal("Request has no deadline"))
+    }
+}
+
+pub async fn timeout_middleware(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(limit) = state.timeouts.timeout_for(request.uri().path()) else {
+        return next.run(request).await;
+    };
+
+    request.extensions_mut().insert(Deadline::new(limit));
+    match timeout(limit, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => AppError::new(
+            StatusCode::GATEWAY_TIMEOUT,
+            format!("Request did not complete within {} ms", limit.as_millis()),
+        )
+        .with_extension("timeout_ms", limit.as_millis() as u64)
+        .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+        time::{Duration, Instant},
+    };
+
+    use axum::{Router, middleware::from_fn_with_state, routing::get};
+    use axum_test::TestServer;
+    use http::StatusCode;
+    use tokio::time::sleep;
+
+    use super::{Deadline, RouteTimeout, Timeouts, timeout_middleware};
+    use crate::{
+        app::AppState,
+        auth::TokenService,
+        error::{AppError, Problem},
+        repository::MemoryRepository,
+    };
+
+    struct DropGuard(Arc<AtomicBool>);
+
+    impl Drop for DropGuard {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn server(dropped: Arc<AtomicBool>) -> TestServer {
+        let state = AppState::new(
+            Arc::new(MemoryRepository::new()),
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        )
+        .with_timeouts(
+            Timeouts::new()
+                .with_default(Duration::from_millis(500))
+                .with_route(RouteTimeout {
+                    prefix: "/slow".to_string(),
+                    timeout_ms: 50,
+                }),
+        );
+
+        let app = Router::new()
+            .route(
+                "/slow",
+                get(move || async move {
+                    let _guard = DropGuard(dropped);
+                    sleep(Duration::from_secs(2)).await;
+                    "done"
+                }),
+            )
+            .route(
+                "/budget",
+                get(|deadline: Deadline| async move {
+                    sleep(Duration::from_millis(20)).await;
+                    format!("{}", deadline.remaining().as_millis())
+                }),
+            )
+            .route(
+                "/query",
+                get(|deadline: Deadline| async move {
+                    deadline.run(sleep(Duration::from_secs(2))).await?;
+                    Ok::<_, AppError>("done")
+                }),
+            )
+            .layer(from_fn_with_state(state.clone(), timeout_middleware))
+            .with_state(state);
+
+        TestServer::new(app).unwrap()
+    }
+
+    #[test]
+    fn test_timeout_for_route() {
+        let timeouts = Timeouts::new()
+            .with_route(RouteTimeout {
+                prefix: "/api".to_string(),
+                timeout_ms: 1000,
+            })
+            .with_route(RouteTimeout {
+                prefix: "/api/uploads".to_string(),
+                timeout_ms: 60000,
+            });
+
+        assert_eq!(
+            timeouts.timeout_for("/api/uploads/1"),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            timeouts.timeout_for("/api/brands"),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(timeouts.timeout_for("/health/live"), None);
+        assert_eq!(
+            timeouts.timeout_for("/api/uploads-archive"),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(timeouts.timeout_for("/apix"), None);
+    }
+
+    #[tokio::test]
+    async fn test_slow_handler_is_cancelled() {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let server = server(dropped.clone());
+        let started = Instant::now();
+
+        let response = server.get("/slow").await;
+        response.assert_status(StatusCode::GATEWAY_TIMEOUT);
+        response.assert_header("Content-Type", "application/problem+json");
+        let problem: Problem = response.json();
+        assert_eq!(problem.status, 504);
+        assert_eq!(problem.extensions["timeout_ms"], 50);
+
+        assert!(dropped.load(Ordering::SeqCst));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_remaining_deadline() {
+        let server = server(Arc::new(AtomicBool::new(false)));
+
+        let remaining: u128 = server.get("/budget").await.text().parse().unwrap();
+        assert!(remaining > 0 && remaining <= 480);
+
+        let response = server.get("/query").await;
+        response.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+        let problem: Problem = response.json();
+        assert_eq!(problem.extensions["timeout_ms"], 500);
+    }
+}
@@ -0,0 +1,451 @@
+use std::{io, path::PathBuf, pin::Pin};
+
+use async_trait::async_trait;
+use axum::{
+    extract::{DefaultBodyLimit, Multipart, State, multipart::MultipartError},
+    middleware::from_fn_with_state,
+};
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::{
+    fs::{self, File},
+    io::{AsyncWrite, AsyncWriteExt},
+};
+use utoipa::ToSchema;
+use utoipa_axum::{router::OpenApiRouter, routes};
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::{AuthUser, auth_middleware},
+    error::{AppError, Problem},
+    extract::Json,
+};
+
+pub type StorageWriter = Pin<Box<dyn AsyncWrite + Send>>;
+
+#[async_trait]
+pub trait FileStorage: Send + Sync {
+    async fn writer(&self, id: &str) -> io::Result<StorageWriter>;
+    async fn remove(&self, id: &str) -> io::Result<()>;
+}
+
+pub struct LocalStorage {
+    directory: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    pub fn path(&self, id: &str) -> PathBuf {
+        self.directory.join(id)
+    }
+}
+
+#[async_trait]
+impl FileStorage for LocalStorage {
+    async fn writer(&self, id: &str) -> io::Result<StorageWriter> {
+        fs::create_dir_all(&self.directory).await?;
+        let file = File::create(self.path(id)).await?;
+
+        Ok(Box::pin(file))
+    }
+
+    async fn remove(&self, id: &str) -> io::Result<()> {
+        fs::remove_file(self.path(id)).await
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct UploadLimits {
+    pub max_file_size: u64,
+    pub max_total_size: u64,
+}
+
+impl Default for UploadLimits {
+    fn default() -> Self {
+        Self {
+            max_file_size: 2 * 1024 * 1024,
+            max_total_size: 5 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct StoredFile {
+    pub id: String,
+    pub field: String,
+    pub file_name: Option<String>,
+    pub content_type: String,
+    pub size: u64,
+    pub checksum: String,
+}
+
+#[allow(dead_code)]
+#[derive(ToSchema)]
+struct ProfilePictureForm {
+    #[schema(value_type = String, format = Binary)]
+    picture: Vec<u8>,
+}
+
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+];
+
+const SNIFF_LENGTH: usize = 12;
+
+pub fn sniff_content_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+
+    SIGNATURES
+        .iter()
+        .find(|(signature, _)| bytes.starts_with(signature))
+        .map(|(_, content_type)| *content_type)
+}
+
+fn multipart_error(err: MultipartError) -> AppError {
+    AppError::new(err.status(), err.body_text())
+}
+
+fn storage_error(err: io::Error) -> AppError {
+    eprintln!("Storage error : {}", err);
+    AppError::internal("Failed to store file")
+}
+
+fn too_large(detail: String) -> AppError {
+    AppError::new(StatusCode::PAYLOAD_TOO_LARGE, detail)
+}
+
+pub fn routes(state: AppState) -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(upload))
+        .layer(DefaultBodyLimit::disable())
+        .route_layer(from_fn_with_state(state, auth_middleware))
+}
+
+#[utoipa::path(
+    post,
+    path = "/profile-picture",
+    tag = "uploads",
+    security(("bearer" = [])),
+    request_body(content = ProfilePictureForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, body = Vec<StoredFile>),
+        (status = 400, description = "Request does not contain any file", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Bearer token is missing or invalid", body = Problem, content_type = "application/problem+json"),
+        (status = 413, description = "A file or the whole upload is too large", body = Problem, content_type = "application/problem+json"),
+        (status = 415, description = "A file is not a supported image", body = Problem, content_type = "application/problem+json")
+    )
+)]
+async fn upload(
+    State(state): State<AppState>,
+    _user: AuthUser,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<Vec<StoredFile>>), AppError> {
+    let limits = state.upload_limits;
+    let mut total: u64 = 0;
+    let mut stored: Vec<StoredFile> = Vec::new();
+
+    let result = async {
+        while let Some(mut field) = multipart.next_field().await.map_err(multipart_error)? {
+            let name = field.name().unwrap_or("").to_string();
+
+            if field.file_name().is_none() {
+                while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+                    total += chunk.len() as u64;
+                    if total > limits.max_total_size {
+                        return Err(too_large(format!(
+                            "Upload exceeds the total limit of {} bytes",
+                            limits.max_total_size
+                        )));
+                    }
+                }
+                continue;
+            }
+
+            let file_name = field.file_name().map(str::to_string);
+            let id = Uuid::now_v7().to_string();
+            let mut head: Vec<u8> = Vec::new();
+            let mut writer: Option<StorageWriter> = None;
+            let mut created = false;
+            let mut content_type = "";
+            let mut hasher = Sha256::new();
+            let mut size: u64 = 0;
+
+            let outcome = async {
+                while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+                    size += chunk.len() as u64;
+                    total += chunk.len() as u64;
+
+                    if size > limits.max_file_size {
+                        return Err(too_large(format!(
+                            "File {} exceeds the limit of {} bytes",
+                            name, limits.max_file_size
+                        )));
+                    }
+                    if total > limits.max_total_size {
+                        return Err(too_large(format!(
+                            "Upload exceeds the total limit of {} bytes",
+                            limits.max_total_size
+                        )));
+                    }
+
+                    hasher.update(&chunk);
+
+                    if writer.is_none() {
+                        head.extend_from_slice(&chunk);
+                        if head.len() < SNIFF_LENGTH {
+                            continue;
+                        }
+
+                        content_type = sniff_content_type(&head).ok_or_else(|| {
+                            AppError::new(
+                                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                                format!("File {} must be a PNG, JPEG, GIF or WebP image", name),
+                            )
+                        })?;
+
+                        let mut opened = state.storage.writer(&id).await.map_err(storage_error)?;
+                        created = true;
+                        opened.write_all(&head).await.map_err(storage_error)?;
+                        writer = Some(opened);
+                        continue;
+                    }
+
+                    if let Some(writer) = writer.as_mut() {
+                        writer.write_all(&chunk).await.map_err(storage_error)?;
+                    }
+                }
+
+                let Some(mut writer) = writer.take() else {
+                    return Err(AppError::new(
+                        StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                        format!("File {} is too small to be an image", name),
+                    ));
+                };
+                writer.shutdown().await.map_err(storage_error)?;
+
+                Ok(())
+            }
+            .await;
+
+            if let Err(err) = outcome {
+                if created {
+                    let _ = state.storage.remove(&id).await;
+                }
+                return Err(err);
+            }
+
+            stored.push(StoredFile {
+                id,
+                field: name,
+                file_name,
+                content_type: content_type.to_string(),
+                size,
+                checksum: format!("{:x}", hasher.finalize()),
+            });
+        }
+
+        Ok(())
+    }
+    .await;
+
+    if let Err(err) = result {
+        for file in &stored {
+            let _ = state.storage.remove(&file.id).await;
+        }
+        return Err(err);
+    }
+
+    if stored.is_empty() {
+        return Err(AppError::new(
+            StatusCode::BAD_REQUEST,
+            "Request does not contain any file",
+        ));
+    }
+
+    Ok((StatusCode::CREATED, Json(stored)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, sync::Arc, time::Duration};
+
+    use axum::body::Bytes;
+    use axum_test::{
+        TestServer,
+        multipart::{MultipartForm, Part},
+    };
+    use http::StatusCode;
+    use sha2::{Digest, Sha256};
+    use uuid::Uuid;
+
+    use super::{LocalStorage, StoredFile, UploadLimits, sniff_content_type};
+    use crate::{
+        app::{AppState, router},
+        auth::TokenService,
+        error::Problem,
+        repository::MemoryRepository,
+    };
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR";
+
+    fn server(limits: UploadLimits) -> (TestServer, PathBuf, String) {
+        let directory = std::env::temp_dir().join(format!("uploads-{}", Uuid::now_v7()));
+        let state = AppState::new(
+            Arc::new(MemoryRepository::new()),
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        )
+        .with_storage(Arc::new(LocalStorage::new(&directory)), limits);
+        let token = state.tokens.issue("rizki").unwrap();
+
+        (TestServer::new(router(state)).unwrap(), directory, token)
+    }
+
+    fn image(size: usize) -> Bytes {
+        let mut bytes = PNG.to_vec();
+        bytes.resize(size, 7);
+        Bytes::from(bytes)
+    }
+
+    fn stored_count(directory: &PathBuf) -> usize {
+        std::fs::read_dir(directory)
+            .map(|entries| entries.count())
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn test_sniff_content_type() {
+        assert_eq!(sniff_content_type(PNG), Some("image/png"));
+        assert_eq!(sniff_content_type(b"\xff\xd8\xff\xe0"), Some("image/jpeg"));
+        assert_eq!(sniff_content_type(b"GIF89a"), Some("image/gif"));
+        assert_eq!(
+            sniff_content_type(b"RIFF\x00\x00\x00\x00WEBPVP8 "),
+            Some("image/webp")
+        );
+        assert_eq!(sniff_content_type(b"<html><body>"), None);
+    }
+
+    #[tokio::test]
+    async fn test_upload_profile_picture() {
+        let (server, directory, token) = server(UploadLimits::default());
+        let content = image(1024);
+
+        let response = server
+            .post("/api/uploads/profile-picture")
+            .authorization_bearer(&token)
+            .multipart(
+                MultipartForm::new()
+                    .add_text("description", "avatar")
+                    .add_part(
+                        "picture",
+                        Part::bytes(content.clone())
+                            .file_name("avatar.txt")
+                            .mime_type("text/plain"),
+                    ),
+            )
+            .await;
+        response.assert_status(StatusCode::CREATED);
+
+        let files: Vec<StoredFile> = response.json();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].field, "picture");
+        assert_eq!(files[0].file_name.as_deref(), Some("avatar.txt"));
+        assert_eq!(files[0].content_type, "image/png");
+        assert_eq!(files[0].size, 1024);
+        assert_eq!(files[0].checksum, format!("{:x}", Sha256::digest(&content)));
+
+        let saved = std::fs::read(directory.join(&files[0].id)).unwrap();
+        assert_eq!(saved, content);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_upload_requires_login() {
+        let (server, directory, _) = server(UploadLimits::default());
+
+        let response = server
+            .post("/api/uploads/profile-picture")
+            .multipart(
+                MultipartForm::new()
+                    .add_part("picture", Part::bytes(image(64)).file_name("avatar.png")),
+            )
+            .await;
+        response.assert_status_unauthorized();
+        assert_eq!(stored_count(&directory), 0);
+    }
+
+    #[tokio::test]
+    async fn test_reject_invalid_upload() {
+        let limits = UploadLimits {
+            max_file_size: 1024,
+            max_total_size: 1536,
+        };
+        let (server, directory, token) = server(limits);
+
+        let response = server
+            .post("/api/uploads/profile-picture")
+            .authorization_bearer(&token)
+            .multipart(
+                MultipartForm::new()
+                    .add_part("picture", Part::bytes(image(1025)).file_name("avatar.png")),
+            )
+            .await;
+        response.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+        let problem: Problem = response.json();
+        assert_eq!(
+            problem.detail,
+            "File picture exceeds the limit of 1024 bytes"
+        );
+
+        let response = server
+            .post("/api/uploads/profile-picture")
+            .authorization_bearer(&token)
+            .multipart(
+                MultipartForm::new()
+                    .add_part("first", Part::bytes(image(1000)).file_name("a.png"))
+                    .add_part("second", Part::bytes(image(1000)).file_name("b.png")),
+            )
+            .await;
+        response.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+        let problem: Problem = response.json();
+        assert_eq!(
+            problem.detail,
+            "Upload exceeds the total limit of 1536 bytes"
+        );
+
+        let response = server
+            .post("/api/uploads/profile-picture")
+            .authorization_bearer(&token)
+            .multipart(
+                MultipartForm::new().add_part(
+                    "picture",
+                    Part::bytes(Bytes::from("<html><body>not an image</body></html>"))
+                        .file_name("avatar.png")
+                        .mime_type("image/png"),
+                ),
+            )
+            .await;
+        response.assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let response = server
+            .post("/api/uploads/profile-picture")
+            .authorization_bearer(&token)
+            .multipart(MultipartForm::new().add_text("description", "avatar"))
+            .await;
+        response.assert_status_bad_request();
+
+        assert_eq!(stored_count(&directory), 0);
+        let _ = std::fs::remove_dir_all(directory);
+    }
+}
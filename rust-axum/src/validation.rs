@@ -0,0 +1,123 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+pub fn not_blank(value: &str) -> Result<(), ValidationError> {
+    if value.trim().is_empty() {
+        return Err(
+            ValidationError::new("not_blank").with_message(Cow::from("value cannot be blank"))
+        );
+    }
+
+    Ok(())
+}
+
+pub fn password_equals_confirm_password(
+    request: &RegisterUserRequest,
+) -> Result<(), ValidationError> {
+    if request.password != request.confirm_password {
+        return Err(ValidationError::new("password_equals_confirm_password")
+            .with_message(Cow::from("password and confirm password must be the same")));
+    }
+
+    Ok(())
+}
+
+pub fn can_register(
+    request: &RegisterUserRequest,
+    context: &DatabaseContext,
+) -> Result<(), ValidationError> {
+    if context.total >= context.max_data {
+        return Err(
+            ValidationError::new("can_register").with_message(Cow::from(format!(
+                "cannot register user {}, database is full",
+                request.name
+            ))),
+        );
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct DatabaseContext {
+    pub total: i32,
+    pub max_data: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct AddressRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub street: String,
+
+    #[validate(length(min = 1, max = 100))]
+    pub city: String,
+
+    #[validate(length(min = 1, max = 100))]
+    pub country: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[validate(context=DatabaseContext,
+    schema(
+        function = "crate::validation::password_equals_confirm_password",
+        skip_on_field_errors = false,
+        code = "password",
+        message = "password != confirm_password"
+    ),
+    schema(
+        function = "crate::validation::can_register",
+        skip_on_field_errors = false,
+        code = "username",
+        use_context
+    )
+)]
+pub struct RegisterUserRequest {
+    #[validate(length(
+        min = 3,
+        max = 20,
+        message = "username must be between 3 and 20 characters"
+    ))]
+    pub username: String,
+
+    #[validate(length(
+        min = 3,
+        max = 20,
+        message = "password must be between 3 and 20 characters"
+    ))]
+    pub password: String,
+
+    pub confirm_password: String,
+
+    #[validate(length(
+        min = 3,
+        max = 100,
+        message = "name must be between 3 and 100 characters"
+    ))]
+    pub name: String,
+
+    #[validate(nested)]
+    pub address: AddressRequest,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct Product {
+    #[validate(length(min = 3, max = 100,))]
+    pub id: String,
+
+    #[validate(length(min = 3, max = 100,))]
+    pub name: String,
+
+    #[validate(nested, length(min = 1,))]
+    pub variants: Vec<ProductVariant>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ProductVariant {
+    #[validate(length(min = 3, max = 100,))]
+    pub name: String,
+
+    #[validate(range(min = 1, max = 1000000000))]
+    pub price: i32,
+}
@@ -0,0 +1,265 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Context;
+use axum::response::{Html, IntoResponse, Response};
+use handlebars::{Handlebars, to_json};
+use http::StatusCode;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{error::AppError, pagination::Page};
+
+pub const EXTENSION: &str = "mustache";
+pub const REQUIRED: [&str; 5] = [
+    "layout/header",
+    "layout/footer",
+    "error",
+    "brands",
+    "categories",
+];
+
+#[derive(Default)]
+pub struct Templates {
+    registry: Handlebars<'static>,
+}
+
+impl Templates {
+    pub fn load(directory: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let directory = directory.as_ref();
+        let mut registry = Handlebars::new();
+
+        for path in template_files(directory)? {
+            let name = path
+                .strip_prefix(directory)?
+                .with_extension("")
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            registry
+                .register_template_file(&name, &path)
+                .with_context(|| format!("Failed to register template {}", path.display()))?;
+        }
+
+        Ok(Self { registry })
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .registry
+            .get_templates()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        names.sort();
+        names
+    }
+
+    pub fn missing(&self, names: &[&str]) -> Vec<String> {
+        names
+            .iter()
+            .filter(|name| !self.registry.has_template(name))
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    pub fn view<T: Serialize>(self: &Arc<Self>, name: &str, context: &T) -> View {
+        View {
+            templates: self.clone(),
+            name: name.to_string(),
+            context: to_json(context),
+        }
+    }
+
+    pub fn error(self: &Arc<Self>, error: impl Into<AppError>) -> ErrorPage {
+        ErrorPage {
+            templates: self.clone(),
+            error: error.into(),
+        }
+    }
+
+    fn render_error(&self, error: AppError) -> Response {
+        match self.registry.render("error", &error.to_problem()) {
+            Ok(body) => (error.status, Html(body)).into_response(),
+            Err(err) => {
+                eprintln!("Failed to render error page : {}", err);
+                error.into_response()
+            }
+        }
+    }
+}
+
+fn template_files(directory: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let entries = fs::read_dir(directory)
+        .with_context(|| format!("Failed to read template directory {}", directory.display()))?;
+
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(template_files(&path)?);
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == EXTENSION)
+        {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
+
+#[derive(Serialize)]
+pub struct Listing<T> {
+    pub title: &'static str,
+    pub page: Page<T>,
+}
+
+pub struct View {
+    templates: Arc<Templates>,
+    name: String,
+    context: Value,
+}
+
+impl IntoResponse for View {
+    fn into_response(self) -> Response {
+        match self.templates.registry.render(&self.name, &self.context) {
+            Ok(body) => Html(body).into_response(),
+            Err(err) => {
+                eprintln!("Failed to render template {} : {}", self.name, err);
+                self.templates.render_error(AppError::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to render page",
+                ))
+            }
+        }
+    }
+}
+
+pub struct ErrorPage {
+    templates: Arc<Templates>,
+    error: AppError,
+}
+
+impl IntoResponse for ErrorPage {
+    fn into_response(self) -> Response {
+        self.templates.render_error(self.error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use axum_test::TestServer;
+    use handlebars::Handlebars;
+    use http::StatusCode;
+
+    use super::{REQUIRED, Templates};
+    use crate::{
+        app::{AppState, router},
+        auth::TokenService,
+        error::PROBLEM_JSON,
+        model::BrandRequest,
+        repository::MemoryRepository,
+    };
+
+    fn server(templates: Templates) -> TestServer {
+        let state = AppState::new(
+            Arc::new(MemoryRepository::new()),
+            TokenService::new(b"secret", Duration::from_secs(60)),
+        )
+        .with_templates(Arc::new(templates));
+        TestServer::new(router(state)).unwrap()
+    }
+
+    #[test]
+    fn test_load_templates() {
+        let templates = Templates::load("templates").unwrap();
+        assert_eq!(
+            templates.names(),
+            vec![
+                "brands",
+                "categories",
+                "error",
+                "layout/footer",
+                "layout/header"
+            ]
+        );
+        assert!(templates.missing(&REQUIRED).is_empty());
+
+        assert!(Templates::load("tidak-ada").is_err());
+        assert_eq!(Templates::default().missing(&["error"]), vec!["error"]);
+    }
+
+    #[tokio::test]
+    async fn test_listing_pages() {
+        let server = server(Templates::load("templates").unwrap());
+        for name in ["Samsung", "<script>alert(1)</script>"] {
+            server
+                .post("/api/brands")
+                .json(&BrandRequest {
+                    name: name.to_string(),
+                    description: None,
+                })
+                .await;
+        }
+
+        let response = server.get("/brands").add_query_param("per_page", 1).await;
+        response.assert_status_ok();
+        response.assert_header("Content-Type", "text/html; charset=utf-8");
+        let html = response.text();
+        assert!(html.contains("<title>Brands</title>"));
+        assert!(html.contains("<td>Samsung</td>"));
+        assert!(html.contains("2 brands"));
+        assert!(html.contains(">Next</a>"));
+        assert!(html.contains("&copy; Rust Axum"));
+
+        let html = server
+            .get("/brands")
+            .add_query_param("page", 2)
+            .add_query_param("per_page", 1)
+            .await
+            .text();
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+
+        let response = server.get("/categories").await;
+        response.assert_status_ok();
+        assert!(response.text().contains("0 categories"));
+    }
+
+    #[tokio::test]
+    async fn test_error_pages() {
+        let server = server(Templates::load("templates").unwrap());
+
+        let response = server.get("/brands").add_query_param("page", "satu").await;
+        response.assert_status_bad_request();
+        response.assert_header("Content-Type", "text/html; charset=utf-8");
+        let html = response.text();
+        assert!(html.contains("<h1>400 Bad Request</h1>"));
+        assert!(html.contains("Query parameter page must be a positive number"));
+        assert!(html.contains("Request id"));
+
+        let mut registry = Handlebars::new();
+        registry
+            .register_template_string("brands", "{{format_price page}}")
+            .unwrap();
+        registry
+            .register_template_string("error", "<h1>{{status}} {{title}}</h1>")
+            .unwrap();
+        let server = self::server(Templates { registry });
+
+        let response = server.get("/brands").await;
+        response.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.text(), "<h1>500 Internal Server Error</h1>");
+
+        let response = self::server(Templates::default()).get("/categories").await;
+        response.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+        response.assert_header("Content-Type", PROBLEM_JSON);
+    }
+}
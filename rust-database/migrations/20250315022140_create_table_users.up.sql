@@ -0,0 +1,5 @@
+-- Add up migration script here
+CREATE TABLE users(
+  username varchar(100) primary key,
+  password varchar(255) not null
+);
\ No newline at end of file
@@ -0,0 +1,7 @@
+-- Add up migration script here
+CREATE TABLE sessions(
+  id varchar(64) primary key,
+  username varchar(100) not null references users(username) on delete cascade,
+  created_at timestamp not null,
+  last_seen_at timestamp not null
+);
\ No newline at end of file